@@ -0,0 +1,751 @@
+//! Webhook alerts on health state transitions
+//!
+//! The monitoring loops report every check; a target becomes unhealthy after
+//! a configurable number of consecutive failures and recovers with the next
+//! success. Transitions (and read-only or host changes) are sent to the
+//! configured webhooks as generic JSON, Slack or `PagerDuty` Events v2 payloads.
+//! Every webhook has its own delivery task that sends events in the order they
+//! happened, retrying failed attempts with exponential backoff.
+
+use crate::metrics::WEBHOOK_DELIVERY_FAILURES;
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{LazyLock, Mutex, PoisonError, RwLock},
+    time::Duration,
+};
+use tokio::{sync::mpsc, task, time};
+
+pub const DEFAULT_FAILURES: u32 = 1;
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Maximum time a single delivery attempt may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub static ALERTS: LazyLock<Alerter> = LazyLock::new(Alerter::default);
+
+/// Kind of state transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// Consecutive failures reached the debounce threshold
+    Unhealthy,
+    /// First success after being unhealthy
+    Recovered,
+    ReadOnly,
+    Writable,
+    /// The server answering the target changed, e.g. after a failover
+    HostChanged,
+}
+
+impl EventKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Unhealthy => "unhealthy",
+            Self::Recovered => "recovered",
+            Self::ReadOnly => "read_only",
+            Self::Writable => "writable",
+            Self::HostChanged => "host_changed",
+        }
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unhealthy" => Ok(Self::Unhealthy),
+            "recovered" => Ok(Self::Recovered),
+            "read_only" => Ok(Self::ReadOnly),
+            "writable" => Ok(Self::Writable),
+            "host_changed" => Ok(Self::HostChanged),
+            _ => Err(format!(
+                "Invalid event: {s} (expected unhealthy, recovered, read_only, writable or host_changed)"
+            )),
+        }
+    }
+}
+
+/// Payload format of a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WebhookFormat {
+    /// The event as a JSON object
+    #[default]
+    Json,
+    /// Slack (and Mattermost/Rocket.Chat) incoming webhook
+    Slack,
+    /// `PagerDuty` Events API v2
+    PagerDuty,
+}
+
+impl FromStr for WebhookFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "slack" => Ok(Self::Slack),
+            "pagerduty" => Ok(Self::PagerDuty),
+            _ => Err(format!(
+                "Invalid webhook format: {s} (expected json, slack or pagerduty)"
+            )),
+        }
+    }
+}
+
+/// A configured webhook receiver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    /// Value of the `webhook` label, the URL may contain secrets
+    pub name: String,
+    pub url: String,
+    pub format: WebhookFormat,
+    /// `PagerDuty` integration key
+    pub routing_key: Option<String>,
+    /// Events sent to this webhook, all if empty
+    pub events: Vec<EventKind>,
+}
+
+impl Webhook {
+    fn wants(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+
+    /// Body of the request announcing `event`
+    fn payload(&self, event: &Event) -> Value {
+        match self.format {
+            WebhookFormat::Json => json!(event),
+            WebhookFormat::Slack => json!({ "text": format!("dbpulse: {}", event.summary()) }),
+            WebhookFormat::PagerDuty => {
+                let (action, severity, dedup) = match event.event {
+                    EventKind::Unhealthy => ("trigger", "critical", "health".to_string()),
+                    EventKind::Recovered => ("resolve", "info", "health".to_string()),
+                    EventKind::ReadOnly => ("trigger", "warning", "read_only".to_string()),
+                    EventKind::Writable => ("resolve", "info", "read_only".to_string()),
+                    // every host change is its own informational alert
+                    EventKind::HostChanged => (
+                        "trigger",
+                        "info",
+                        format!("host/{}", event.time.timestamp()),
+                    ),
+                };
+                json!({
+                    "routing_key": self.routing_key,
+                    "event_action": action,
+                    "dedup_key": format!("dbpulse/{}/{dedup}", event.target),
+                    "payload": {
+                        "summary": event.summary(),
+                        "source": event.target,
+                        "severity": severity,
+                        "timestamp": event.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+                        "component": event.database,
+                        "custom_details": event,
+                    },
+                })
+            }
+        }
+    }
+}
+
+/// Alert settings, resolved from the `alerts` section of the configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertConfig {
+    /// Consecutive failed checks before a target is reported unhealthy
+    pub failures: u32,
+    /// Delivery attempts after the first failed one
+    pub retries: u32,
+    /// Delay before the first retry, doubled on every further retry
+    pub backoff: Duration,
+    pub webhooks: Vec<Webhook>,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            failures: DEFAULT_FAILURES,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+/// A state transition of a target, the body of `json` webhooks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Event {
+    pub event: EventKind,
+    pub target: String,
+    pub database: String,
+    pub time: DateTime<Utc>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_host: Option<String>,
+    /// Consecutive failed checks, for `unhealthy` and `recovered`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failures: Option<u32>,
+}
+
+impl Event {
+    /// One-line description used by Slack and `PagerDuty`
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let target = &self.target;
+        match self.event {
+            EventKind::Unhealthy => format!(
+                "{target} is unhealthy after {} failed checks: {}",
+                self.failures.unwrap_or_default(),
+                self.message
+            ),
+            EventKind::Recovered => format!("{target} recovered"),
+            EventKind::ReadOnly => format!("{target} is read-only"),
+            EventKind::Writable => format!("{target} is writable again"),
+            EventKind::HostChanged => format!(
+                "{target} is now served by {} (was {})",
+                self.host.as_deref().unwrap_or("unknown"),
+                self.previous_host.as_deref().unwrap_or("unknown")
+            ),
+        }
+    }
+}
+
+/// Outcome of one check, as reported by the monitoring loop
+#[derive(Debug, Clone, Copy)]
+pub struct Observation<'a> {
+    pub time: DateTime<Utc>,
+    /// Error message if the check failed
+    pub error: Option<&'a str>,
+    /// Whether the database was read-only, only known if the check succeeded
+    pub read_only: bool,
+    pub host: Option<&'a str>,
+}
+
+/// Health of a target as seen by the alerts
+#[derive(Debug, Default, Clone)]
+struct TargetState {
+    failures: u32,
+    unhealthy: bool,
+    read_only: Option<bool>,
+    host: Option<String>,
+}
+
+impl TargetState {
+    /// Update the state with a check, returns the transitions it caused
+    fn observe(&mut self, threshold: u32, observation: Observation<'_>) -> Vec<Transition> {
+        let mut transitions = Vec::new();
+
+        if let Some(error) = observation.error {
+            self.failures = self.failures.saturating_add(1);
+            if !self.unhealthy && self.failures >= threshold {
+                self.unhealthy = true;
+                transitions.push(Transition::new(EventKind::Unhealthy, error.to_string()));
+            }
+            return transitions;
+        }
+
+        if self.unhealthy {
+            self.unhealthy = false;
+            transitions.push(Transition::new(
+                EventKind::Recovered,
+                format!("check succeeded after {} failures", self.failures),
+            ));
+        }
+        self.failures = 0;
+
+        // the first check is the baseline, a replica is read-only by design
+        match (self.read_only, observation.read_only) {
+            (Some(false), true) => transitions.push(Transition::new(
+                EventKind::ReadOnly,
+                "database is in read-only mode".to_string(),
+            )),
+            (Some(true), false) => transitions.push(Transition::new(
+                EventKind::Writable,
+                "database accepts writes again".to_string(),
+            )),
+            _ => {}
+        }
+        self.read_only = Some(observation.read_only);
+
+        if let Some(host) = observation.host {
+            if let Some(previous) = self.host.as_deref()
+                && previous != host
+            {
+                transitions.push(Transition {
+                    kind: EventKind::HostChanged,
+                    message: format!("host changed from {previous} to {host}"),
+                    previous_host: Some(previous.to_string()),
+                });
+            }
+            self.host = Some(host.to_string());
+        }
+
+        transitions
+    }
+}
+
+struct Transition {
+    kind: EventKind,
+    message: String,
+    previous_host: Option<String>,
+}
+
+impl Transition {
+    const fn new(kind: EventKind, message: String) -> Self {
+        Self {
+            kind,
+            message,
+            previous_host: None,
+        }
+    }
+}
+
+/// A payload waiting in the queue of a webhook
+struct Delivery {
+    webhook: Webhook,
+    payload: Value,
+    retries: u32,
+    backoff: Duration,
+}
+
+/// Tracks the state of every target and notifies the webhooks
+#[derive(Default)]
+pub struct Alerter {
+    config: RwLock<AlertConfig>,
+    targets: RwLock<HashMap<String, TargetState>>,
+    /// Delivery queue of every webhook, keyed by name
+    queues: Mutex<HashMap<String, mpsc::UnboundedSender<Delivery>>>,
+    client: reqwest::Client,
+}
+
+impl Alerter {
+    /// Replace the alert settings, the state of every target is kept
+    ///
+    /// Queues of removed webhooks are closed once their pending deliveries
+    /// are sent.
+    pub fn configure(&self, config: AlertConfig) {
+        self.queues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|name, _| config.webhooks.iter().any(|webhook| webhook.name == *name));
+        *self.config.write().unwrap_or_else(PoisonError::into_inner) = config;
+    }
+
+    /// Forget a target that is no longer monitored
+    pub fn remove(&self, target: &str) {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(target);
+    }
+
+    /// Record a check and send an event for every transition it caused
+    pub fn observe(&self, target: &str, database: &str, observation: Observation<'_>) {
+        for event in self.events(target, database, observation) {
+            self.notify(&event);
+        }
+    }
+
+    fn events(&self, target: &str, database: &str, observation: Observation<'_>) -> Vec<Event> {
+        let threshold = self
+            .config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .failures;
+        let mut targets = self.targets.write().unwrap_or_else(PoisonError::into_inner);
+        let state = targets.entry(target.to_string()).or_default();
+        let failures = state.failures;
+
+        state
+            .observe(threshold, observation)
+            .into_iter()
+            .map(|transition| Event {
+                event: transition.kind,
+                target: target.to_string(),
+                database: database.to_string(),
+                time: observation.time,
+                message: transition.message,
+                host: observation.host.map(str::to_string),
+                previous_host: transition.previous_host,
+                failures: match transition.kind {
+                    EventKind::Unhealthy => Some(state.failures),
+                    EventKind::Recovered => Some(failures),
+                    _ => None,
+                },
+            })
+            .collect()
+    }
+
+    /// Queue an event for every interested webhook, delivered in the background
+    fn notify(&self, event: &Event) {
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let mut queues = self.queues.lock().unwrap_or_else(PoisonError::into_inner);
+        for webhook in config
+            .webhooks
+            .into_iter()
+            .filter(|webhook| webhook.wants(event.event))
+        {
+            let queue = queues
+                .entry(webhook.name.clone())
+                .or_insert_with(|| spawn_worker(self.client.clone()));
+            if queue.is_closed() {
+                *queue = spawn_worker(self.client.clone());
+            }
+            let _ = queue.send(Delivery {
+                payload: webhook.payload(event),
+                webhook,
+                retries: config.retries,
+                backoff: config.backoff,
+            });
+        }
+    }
+}
+
+/// Start the task delivering the queue of a webhook, one payload at a time
+///
+/// A payload is only sent once the previous one was delivered or given up
+/// on, so a retried trigger never reaches the receiver after its resolve.
+fn spawn_worker(client: reqwest::Client) -> mpsc::UnboundedSender<Delivery> {
+    let (queue, mut deliveries) = mpsc::unbounded_channel::<Delivery>();
+    task::spawn(async move {
+        while let Some(Delivery {
+            webhook,
+            payload,
+            retries,
+            backoff,
+        }) = deliveries.recv().await
+        {
+            if let Err(e) = deliver(&client, &webhook.url, &payload, retries, backoff).await {
+                eprintln!("Failed to deliver alert to webhook {}: {e:#}", webhook.name);
+                WEBHOOK_DELIVERY_FAILURES
+                    .with_label_values(&[webhook.name.as_str()])
+                    .inc();
+            }
+        }
+    });
+    queue
+}
+
+/// POST a payload, retrying failed attempts with exponential backoff
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &Value,
+    retries: u32,
+    backoff: Duration,
+) -> Result<()> {
+    let mut delay = backoff;
+    let mut attempt = 0;
+    loop {
+        let error = match client
+            .post(url)
+            .timeout(REQUEST_TIMEOUT)
+            .json(payload)
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => anyhow!("HTTP {}", response.status()),
+            Err(e) => anyhow!(e),
+        };
+
+        if attempt >= retries {
+            bail!("{error} (after {} attempts)", attempt + 1);
+        }
+        attempt += 1;
+        time::sleep(delay).await;
+        delay = delay.saturating_mul(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+    use std::sync::{
+        Arc, Mutex,
+        atomic::{AtomicU32, Ordering},
+    };
+    use tokio::net::TcpListener;
+
+    fn ok(host: &str) -> Observation<'_> {
+        Observation {
+            time: Utc::now(),
+            error: None,
+            read_only: false,
+            host: Some(host),
+        }
+    }
+
+    fn failed(error: &str) -> Observation<'_> {
+        Observation {
+            time: Utc::now(),
+            error: Some(error),
+            read_only: false,
+            host: None,
+        }
+    }
+
+    fn kinds(transitions: &[Transition]) -> Vec<EventKind> {
+        transitions.iter().map(|t| t.kind).collect()
+    }
+
+    fn event(kind: EventKind) -> Event {
+        Event {
+            event: kind,
+            target: "orders".to_string(),
+            database: "postgres".to_string(),
+            time: Utc::now(),
+            message: "connection refused".to_string(),
+            host: Some("10.0.0.2".to_string()),
+            previous_host: Some("10.0.0.1".to_string()),
+            failures: Some(3),
+        }
+    }
+
+    fn webhook(format: WebhookFormat) -> Webhook {
+        Webhook {
+            name: "ops".to_string(),
+            url: "http://127.0.0.1:1/".to_string(),
+            format,
+            routing_key: Some("R0UT1NG".to_string()),
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_debounce_and_recovery() {
+        let mut state = TargetState::default();
+        assert!(state.observe(3, ok("db-1")).is_empty());
+        assert!(state.observe(3, failed("refused")).is_empty());
+        assert!(state.observe(3, failed("refused")).is_empty());
+        assert_eq!(
+            kinds(&state.observe(3, failed("refused"))),
+            vec![EventKind::Unhealthy]
+        );
+        // no repeated alerts while still failing
+        assert!(state.observe(3, failed("refused")).is_empty());
+        assert_eq!(
+            kinds(&state.observe(3, ok("db-1"))),
+            vec![EventKind::Recovered]
+        );
+        assert!(state.observe(3, ok("db-1")).is_empty());
+    }
+
+    #[test]
+    fn test_failures_below_threshold_are_not_reported() {
+        let mut state = TargetState::default();
+        assert!(state.observe(2, failed("refused")).is_empty());
+        assert!(state.observe(2, ok("db-1")).is_empty());
+        assert!(state.observe(2, failed("refused")).is_empty());
+    }
+
+    #[test]
+    fn test_read_only_and_host_changes() {
+        let mut state = TargetState::default();
+        assert!(state.observe(1, ok("db-1")).is_empty());
+
+        let read_only = Observation {
+            read_only: true,
+            ..ok("db-2")
+        };
+        let transitions = state.observe(1, read_only);
+        assert_eq!(
+            kinds(&transitions),
+            vec![EventKind::ReadOnly, EventKind::HostChanged]
+        );
+        assert_eq!(
+            transitions.last().unwrap().previous_host.as_deref(),
+            Some("db-1")
+        );
+        assert_eq!(
+            kinds(&state.observe(1, ok("db-2"))),
+            vec![EventKind::Writable]
+        );
+        // an unknown host is not a change
+        let unknown = Observation {
+            host: None,
+            ..ok("db-2")
+        };
+        assert!(state.observe(1, unknown).is_empty());
+    }
+
+    #[test]
+    fn test_replica_first_check_is_baseline() {
+        let mut state = TargetState::default();
+        let replica = Observation {
+            read_only: true,
+            ..ok("replica-1")
+        };
+        assert!(state.observe(1, replica).is_empty());
+        assert!(state.observe(1, replica).is_empty());
+        assert_eq!(
+            kinds(&state.observe(1, ok("replica-1"))),
+            vec![EventKind::Writable]
+        );
+    }
+
+    #[test]
+    fn test_event_kind_from_str() {
+        assert_eq!(
+            "host_changed".parse::<EventKind>().unwrap(),
+            EventKind::HostChanged
+        );
+        assert!("down".parse::<EventKind>().is_err());
+        assert_eq!(
+            "PagerDuty".parse::<WebhookFormat>().unwrap(),
+            WebhookFormat::PagerDuty
+        );
+        assert!("teams".parse::<WebhookFormat>().is_err());
+    }
+
+    #[test]
+    fn test_json_payload() {
+        let payload = webhook(WebhookFormat::Json).payload(&event(EventKind::Unhealthy));
+        assert_eq!(payload.pointer("/event").unwrap(), "unhealthy");
+        assert_eq!(payload.pointer("/target").unwrap(), "orders");
+        assert_eq!(payload.pointer("/failures").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_slack_payload() {
+        let payload = webhook(WebhookFormat::Slack).payload(&event(EventKind::Unhealthy));
+        assert_eq!(
+            payload.pointer("/text").unwrap(),
+            "dbpulse: orders is unhealthy after 3 failed checks: connection refused"
+        );
+    }
+
+    #[test]
+    fn test_pagerduty_payload() {
+        let webhook = webhook(WebhookFormat::PagerDuty);
+        let trigger = webhook.payload(&event(EventKind::Unhealthy));
+        assert_eq!(trigger.pointer("/routing_key").unwrap(), "R0UT1NG");
+        assert_eq!(trigger.pointer("/event_action").unwrap(), "trigger");
+        assert_eq!(trigger.pointer("/payload/severity").unwrap(), "critical");
+        assert_eq!(trigger.pointer("/payload/source").unwrap(), "orders");
+
+        let resolve = webhook.payload(&event(EventKind::Recovered));
+        assert_eq!(resolve.pointer("/event_action").unwrap(), "resolve");
+        // recovery resolves the incident opened by the failure
+        assert_eq!(
+            resolve.pointer("/dedup_key").unwrap(),
+            trigger.pointer("/dedup_key").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_webhook_event_filter() {
+        let webhook = Webhook {
+            events: vec![EventKind::Unhealthy, EventKind::Recovered],
+            ..webhook(WebhookFormat::Json)
+        };
+        assert!(webhook.wants(EventKind::Recovered));
+        assert!(!webhook.wants(EventKind::HostChanged));
+    }
+
+    /// Receiver failing the first `failures` requests, recording the rest
+    async fn receiver(failures: u32) -> (String, Arc<Mutex<Vec<Value>>>, Arc<AtomicU32>) {
+        #[derive(Clone)]
+        struct Receiver {
+            bodies: Arc<Mutex<Vec<Value>>>,
+            requests: Arc<AtomicU32>,
+            failures: u32,
+        }
+
+        async fn handler(State(receiver): State<Receiver>, Json(body): Json<Value>) -> StatusCode {
+            if receiver.requests.fetch_add(1, Ordering::SeqCst) < receiver.failures {
+                return StatusCode::SERVICE_UNAVAILABLE;
+            }
+            receiver.bodies.lock().unwrap().push(body);
+            StatusCode::OK
+        }
+
+        let state = Receiver {
+            bodies: Arc::default(),
+            requests: Arc::default(),
+            failures,
+        };
+        let (bodies, requests) = (state.bodies.clone(), state.requests.clone());
+        let app = Router::new()
+            .route("/hook", post(handler))
+            .with_state(state);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        task::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, bodies, requests)
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries() {
+        let (url, bodies, requests) = receiver(2).await;
+        let client = reqwest::Client::new();
+        let payload = json!({ "event": "unhealthy" });
+
+        deliver(&client, &url, &payload, 2, Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert_eq!(bodies.lock().unwrap().as_slice(), &[payload]);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_gives_up() {
+        let (url, bodies, requests) = receiver(u32::MAX).await;
+        let client = reqwest::Client::new();
+
+        let err = deliver(&client, &url, &json!({}), 1, Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("503"), "{err}");
+        assert!(err.to_string().contains("after 2 attempts"), "{err}");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert!(bodies.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alerter_notifies_webhooks() {
+        // the first attempt fails, so the trigger is retried after its resolve was raised
+        let (url, bodies, _) = receiver(1).await;
+        let alerter = Alerter::default();
+        alerter.configure(AlertConfig {
+            failures: 2,
+            backoff: Duration::from_millis(50),
+            webhooks: vec![Webhook {
+                url,
+                ..webhook(WebhookFormat::Json)
+            }],
+            ..AlertConfig::default()
+        });
+
+        alerter.observe("alert-db", "mysql", failed("refused"));
+        alerter.observe("alert-db", "mysql", failed("refused"));
+        alerter.observe("alert-db", "mysql", ok("db-1"));
+
+        for _ in 0..100 {
+            if bodies.lock().unwrap().len() == 2 {
+                break;
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        let bodies = bodies.lock().unwrap();
+        let events: Vec<&str> = bodies
+            .iter()
+            .map(|body| body.pointer("/event").unwrap().as_str().unwrap())
+            .collect();
+        assert_eq!(events, vec!["unhealthy", "recovered"]);
+    }
+}
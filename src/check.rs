@@ -0,0 +1,638 @@
+//! One-shot check with Nagios/Icinga compatible output
+//!
+//! `dbpulse check` runs the health check of every configured target exactly
+//! once, without the HTTP server or the monitoring loop. It prints a single
+//! summary line with performance data, followed by one line per target, and
+//! exits with 0/1/2/3 for OK/WARNING/CRITICAL/UNKNOWN.
+
+use crate::{
+    config::{Config, TargetConfig},
+    pulse::{is_database_read_only, run_health_check},
+    queries::{HealthCheckResult, Session},
+    tls::cache::CertCache,
+};
+use anyhow::{Result, bail};
+use chrono::Utc;
+use futures::future::join_all;
+use std::{fmt, time::Instant};
+
+/// Name of the subcommand
+pub const COMMAND: &str = "check";
+
+/// Plugin state, ordered by severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum State {
+    Ok,
+    Warning,
+    Unknown,
+    Critical,
+}
+
+impl State {
+    /// Exit code expected by Nagios and Icinga
+    #[must_use]
+    pub const fn exit_code(self) -> u8 {
+        match self {
+            Self::Ok => 0,
+            Self::Warning => 1,
+            Self::Critical => 2,
+            Self::Unknown => 3,
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ok => "OK",
+            Self::Warning => "WARNING",
+            Self::Critical => "CRITICAL",
+            Self::Unknown => "UNKNOWN",
+        })
+    }
+}
+
+/// Warning and critical thresholds of a single measurement
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    pub warning: Option<f64>,
+    pub critical: Option<f64>,
+}
+
+impl Limits {
+    /// Breached state and limit of a value that gets worse as it grows
+    fn above(self, value: f64) -> Option<(State, f64)> {
+        match (self.critical, self.warning) {
+            (Some(critical), _) if value > critical => Some((State::Critical, critical)),
+            (_, Some(warning)) if value > warning => Some((State::Warning, warning)),
+            _ => None,
+        }
+    }
+
+    /// Breached state and limit of a value that gets worse as it shrinks
+    fn below(self, value: f64) -> Option<(State, f64)> {
+        match (self.critical, self.warning) {
+            (Some(critical), _) if value <= critical => Some((State::Critical, critical)),
+            (_, Some(warning)) if value <= warning => Some((State::Warning, warning)),
+            _ => None,
+        }
+    }
+
+    /// `;warn;crit` suffix of a performance data entry
+    fn perfdata(self) -> String {
+        let limit = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        format!(";{};{}", limit(self.warning), limit(self.critical))
+    }
+
+    fn validate(self, name: &str, ascending: bool) -> Result<()> {
+        for value in [self.warning, self.critical].into_iter().flatten() {
+            if !value.is_finite() || value < 0.0 {
+                bail!("{name} thresholds must be positive numbers, got {value}");
+            }
+        }
+        if let (Some(warning), Some(critical)) = (self.warning, self.critical)
+            && ((ascending && warning > critical) || (!ascending && warning < critical))
+        {
+            let order = if ascending { "above" } else { "below" };
+            bail!("critical {name} threshold {critical} must not be {order} warning {warning}");
+        }
+        Ok(())
+    }
+}
+
+/// Thresholds evaluated against every target
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Thresholds {
+    /// Runtime of the whole check in milliseconds
+    pub latency_ms: Limits,
+    /// Replication lag in seconds, only evaluated on replicas
+    pub lag_seconds: Limits,
+    /// Days until the server certificate expires, only evaluated with TLS
+    pub cert_expiry_days: Limits,
+    /// Report read-only databases as CRITICAL instead of grading their lag
+    pub require_writable: bool,
+}
+
+impl Thresholds {
+    /// Validate that thresholds are positive and warnings trigger first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the offending threshold
+    pub fn validate(&self) -> Result<()> {
+        self.latency_ms.validate("latency", true)?;
+        self.lag_seconds.validate("lag", true)?;
+        self.cert_expiry_days.validate("cert expiry", false)
+    }
+}
+
+/// Outcome of checking a single target
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetReport {
+    pub target: String,
+    pub state: State,
+    pub summary: String,
+    pub runtime_ms: Option<u128>,
+    pub lag_seconds: Option<f64>,
+    pub cert_expiry_days: Option<i64>,
+}
+
+impl TargetReport {
+    fn unknown(target: &str, summary: String) -> Self {
+        Self {
+            target: target.to_string(),
+            state: State::Unknown,
+            summary,
+            runtime_ms: None,
+            lag_seconds: None,
+            cert_expiry_days: None,
+        }
+    }
+
+    /// Evaluate the outcome of a health check against the thresholds
+    #[must_use]
+    pub fn evaluate(
+        target: &str,
+        database: &str,
+        outcome: &Result<HealthCheckResult>,
+        runtime_ms: u128,
+        thresholds: &Thresholds,
+    ) -> Self {
+        let result = match outcome {
+            Ok(result) => result,
+            Err(error) => {
+                return Self {
+                    target: target.to_string(),
+                    state: State::Critical,
+                    summary: format!("{error:#}"),
+                    runtime_ms: Some(runtime_ms),
+                    lag_seconds: None,
+                    cert_expiry_days: None,
+                };
+            }
+        };
+        let cert_expiry_days = result
+            .tls_metadata
+            .as_ref()
+            .and_then(|metadata| metadata.cert_expiry_days);
+
+        let mut state =
+            if thresholds.require_writable && is_database_read_only(database, &result.version) {
+                State::Critical
+            } else {
+                State::Ok
+            };
+        let mut details = vec![format!("runtime {runtime_ms}ms")];
+        if let Some((breached, limit)) = thresholds.latency_ms.above(as_f64(runtime_ms)) {
+            state = state.max(breached);
+            details.push(format!("runtime above {limit}ms"));
+        }
+        if let Some(lag) = result.replication_lag_seconds {
+            details.push(format!("replication lag {lag:.3}s"));
+            if let Some((breached, limit)) = thresholds.lag_seconds.above(lag) {
+                state = state.max(breached);
+                details.push(format!("replication lag above {limit}s"));
+            }
+        }
+        if let Some(days) = cert_expiry_days {
+            details.push(format!("certificate expires in {days} days"));
+            if let Some((breached, limit)) = thresholds.cert_expiry_days.below(as_f64(days)) {
+                state = state.max(breached);
+                details.push(format!("certificate expiry within {limit} days"));
+            }
+        }
+
+        Self {
+            target: target.to_string(),
+            state,
+            summary: format!("{}, {}", result.version, details.join(", ")),
+            runtime_ms: Some(runtime_ms),
+            lag_seconds: result.replication_lag_seconds,
+            cert_expiry_days,
+        }
+    }
+
+    /// Performance data entries, prefixed with the target name if `prefixed`
+    fn perfdata(&self, thresholds: &Thresholds, prefixed: bool) -> Vec<String> {
+        let label = |name: &str| {
+            if prefixed {
+                format!("'{} {name}'", self.target.replace('\'', "''"))
+            } else {
+                name.to_string()
+            }
+        };
+        let mut entries = Vec::new();
+        if let Some(runtime) = self.runtime_ms {
+            entries.push(format!(
+                "{}={runtime}ms{};0",
+                label("runtime"),
+                thresholds.latency_ms.perfdata()
+            ));
+        }
+        if let Some(lag) = self.lag_seconds {
+            entries.push(format!(
+                "{}={lag:.3}s{};0",
+                label("replication_lag"),
+                thresholds.lag_seconds.perfdata()
+            ));
+        }
+        if let Some(days) = self.cert_expiry_days {
+            entries.push(format!(
+                "{}={days}{}",
+                label("cert_expiry_days"),
+                thresholds.cert_expiry_days.perfdata()
+            ));
+        }
+        entries
+    }
+}
+
+/// Reports of every checked target
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub targets: Vec<TargetReport>,
+    pub thresholds: Thresholds,
+}
+
+impl Report {
+    /// Worst state of all targets, `Unknown` if nothing was checked
+    #[must_use]
+    pub fn state(&self) -> State {
+        self.targets
+            .iter()
+            .map(|report| report.state)
+            .max()
+            .unwrap_or(State::Unknown)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefixed = self.targets.len() > 1;
+        let perfdata: Vec<String> = self
+            .targets
+            .iter()
+            .flat_map(|report| report.perfdata(&self.thresholds, prefixed))
+            .collect();
+
+        write!(f, "DBPULSE {} - ", self.state())?;
+        match self.targets.as_slice() {
+            [] => f.write_str("no targets configured")?,
+            [report] => write!(f, "{}: {}", report.target, report.summary)?,
+            reports => {
+                let counts: Vec<String> =
+                    [State::Critical, State::Warning, State::Unknown, State::Ok]
+                        .into_iter()
+                        .filter_map(|state| {
+                            let count = reports.iter().filter(|r| r.state == state).count();
+                            (count > 0)
+                                .then(|| format!("{count} {}", state.to_string().to_lowercase()))
+                        })
+                        .collect();
+                write!(f, "{} targets: {}", reports.len(), counts.join(", "))?;
+            }
+        }
+        if !perfdata.is_empty() {
+            write!(f, " | {}", perfdata.join(" "))?;
+        }
+        if prefixed {
+            for report in &self.targets {
+                write!(
+                    f,
+                    "\n{} - {}: {}",
+                    report.state, report.target, report.summary
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Convert for comparison with a threshold, saturating at the `i32` range
+fn as_f64<T: TryInto<i32> + PartialOrd + Default>(value: T) -> f64 {
+    let saturated = if value < T::default() {
+        i32::MIN
+    } else {
+        i32::MAX
+    };
+    value.try_into().map_or(f64::from(saturated), f64::from)
+}
+
+async fn check_target(
+    config: &TargetConfig,
+    cert_cache: &CertCache,
+    thresholds: &Thresholds,
+) -> TargetReport {
+    let target = match config.build() {
+        Ok(target) => target,
+        Err(error) => return TargetReport::unknown(&config.name, format!("{error:#}")),
+    };
+    let Some(database) = target.database() else {
+        return TargetReport::unknown(
+            &target.name,
+            format!("unsupported driver {}", target.dsn.driver),
+        );
+    };
+
+    let mut session = Session::default();
+    let start = Instant::now();
+    let outcome = run_health_check(database, &target, Utc::now(), cert_cache, &mut session).await;
+    let runtime_ms = start.elapsed().as_millis();
+    session.close().await;
+    TargetReport::evaluate(&target.name, database, &outcome, runtime_ms, thresholds)
+}
+
+/// Check every target once and print the report
+pub async fn execute(config: Config, thresholds: Thresholds) -> State {
+    let cert_cache = CertCache::new(std::time::Duration::from_secs(config.tls_cert_cache_ttl));
+    let targets = join_all(
+        config
+            .targets
+            .iter()
+            .map(|target| check_target(target, &cert_cache, &thresholds)),
+    )
+    .await;
+
+    let report = Report {
+        targets,
+        thresholds,
+    };
+    println!("{report}");
+    report.state()
+}
+
+/// Print an UNKNOWN result and exit, for errors before any target was checked
+pub fn exit_unknown(error: impl fmt::Display) -> ! {
+    println!("DBPULSE {} - {error}", State::Unknown);
+    std::process::exit(State::Unknown.exit_code().into())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::tls::TlsMetadata;
+    use anyhow::anyhow;
+
+    fn result(version: &str) -> HealthCheckResult {
+        HealthCheckResult {
+            version: version.to_string(),
+            flavor: "postgres",
+            db_host: None,
+            uptime_seconds: None,
+            replication_lag_seconds: None,
+            tls_metadata: None,
+        }
+    }
+
+    fn limits(warning: f64, critical: f64) -> Limits {
+        Limits {
+            warning: Some(warning),
+            critical: Some(critical),
+        }
+    }
+
+    #[test]
+    fn test_state_exit_codes_and_severity() {
+        assert_eq!(State::Ok.exit_code(), 0);
+        assert_eq!(State::Warning.exit_code(), 1);
+        assert_eq!(State::Critical.exit_code(), 2);
+        assert_eq!(State::Unknown.exit_code(), 3);
+        assert!(State::Critical > State::Unknown);
+        assert!(State::Unknown > State::Warning);
+        assert!(State::Warning > State::Ok);
+    }
+
+    #[test]
+    fn test_limits() {
+        let latency = limits(500.0, 1000.0);
+        assert_eq!(latency.above(500.0), None);
+        assert_eq!(latency.above(501.0), Some((State::Warning, 500.0)));
+        assert_eq!(latency.above(1001.0), Some((State::Critical, 1000.0)));
+
+        let expiry = limits(14.0, 7.0);
+        assert_eq!(expiry.below(15.0), None);
+        assert_eq!(expiry.below(14.0), Some((State::Warning, 14.0)));
+        assert_eq!(expiry.below(-1.0), Some((State::Critical, 7.0)));
+
+        assert_eq!(Limits::default().above(f64::MAX), None);
+        assert_eq!(latency.perfdata(), ";500;1000");
+        assert_eq!(Limits::default().perfdata(), ";;");
+    }
+
+    #[test]
+    fn test_thresholds_validate() {
+        assert!(Thresholds::default().validate().is_ok());
+        assert!(
+            Thresholds {
+                latency_ms: limits(500.0, 1000.0),
+                lag_seconds: limits(10.0, 10.0),
+                cert_expiry_days: limits(14.0, 7.0),
+                ..Thresholds::default()
+            }
+            .validate()
+            .is_ok()
+        );
+
+        let err = Thresholds {
+            latency_ms: limits(1000.0, 500.0),
+            ..Thresholds::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert!(err.to_string().contains("latency"), "{err}");
+
+        let err = Thresholds {
+            cert_expiry_days: limits(7.0, 14.0),
+            ..Thresholds::default()
+        }
+        .validate()
+        .unwrap_err();
+        assert!(err.to_string().contains("cert expiry"), "{err}");
+
+        let negative = Thresholds {
+            lag_seconds: Limits {
+                warning: Some(-1.0),
+                critical: None,
+            },
+            ..Thresholds::default()
+        };
+        assert!(negative.validate().is_err());
+    }
+
+    #[test]
+    fn test_evaluate_ok() {
+        let report = TargetReport::evaluate(
+            "orders",
+            "postgres",
+            &Ok(result("PostgreSQL 17.0")),
+            42,
+            &Thresholds {
+                latency_ms: limits(500.0, 1000.0),
+                ..Thresholds::default()
+            },
+        );
+        assert_eq!(report.state, State::Ok);
+        assert_eq!(report.summary, "PostgreSQL 17.0, runtime 42ms");
+    }
+
+    #[test]
+    fn test_evaluate_error_is_critical() {
+        let report = TargetReport::evaluate(
+            "orders",
+            "postgres",
+            &Err(anyhow!("connection refused")),
+            3,
+            &Thresholds::default(),
+        );
+        assert_eq!(report.state, State::Critical);
+        assert_eq!(report.summary, "connection refused");
+        assert_eq!(report.runtime_ms, Some(3));
+    }
+
+    #[test]
+    fn test_evaluate_replica_lag() {
+        let replica = Ok(HealthCheckResult {
+            replication_lag_seconds: Some(2.5),
+            ..result("PostgreSQL 17.0 - Database is in recovery mode")
+        });
+        let thresholds = Thresholds {
+            lag_seconds: limits(10.0, 60.0),
+            ..Thresholds::default()
+        };
+
+        let report = TargetReport::evaluate("replica", "postgres", &replica, 10, &thresholds);
+        assert_eq!(report.state, State::Ok);
+        assert_eq!(report.lag_seconds, Some(2.5));
+        assert!(report.summary.contains("replication lag 2.500s"));
+
+        let lagging = Thresholds {
+            lag_seconds: limits(1.0, 60.0),
+            ..thresholds
+        };
+        let report = TargetReport::evaluate("replica", "postgres", &replica, 10, &lagging);
+        assert_eq!(report.state, State::Warning);
+        assert!(report.summary.contains("replication lag above 1s"));
+
+        let writable = Thresholds {
+            require_writable: true,
+            ..thresholds
+        };
+        let report = TargetReport::evaluate("replica", "postgres", &replica, 10, &writable);
+        assert_eq!(report.state, State::Critical);
+    }
+
+    #[test]
+    fn test_evaluate_thresholds() {
+        let thresholds = Thresholds {
+            latency_ms: limits(500.0, 1000.0),
+            lag_seconds: limits(10.0, 60.0),
+            cert_expiry_days: limits(30.0, 7.0),
+            ..Thresholds::default()
+        };
+        let mut ok = result("MySQL 8.4.0");
+        ok.tls_metadata = Some(TlsMetadata {
+            cert_expiry_days: Some(20),
+            ..TlsMetadata::default()
+        });
+
+        let report = TargetReport::evaluate("app", "mysql", &Ok(ok.clone()), 600, &thresholds);
+        assert_eq!(report.state, State::Warning);
+        assert!(report.summary.contains("runtime above 500ms"));
+        assert!(report.summary.contains("certificate expiry within 30 days"));
+
+        let report = TargetReport::evaluate("app", "mysql", &Ok(ok), 1200, &thresholds);
+        assert_eq!(report.state, State::Critical);
+        assert!(report.summary.contains("runtime above 1000ms"));
+    }
+
+    #[test]
+    fn test_report_single_target() {
+        let thresholds = Thresholds {
+            latency_ms: limits(500.0, 1000.0),
+            ..Thresholds::default()
+        };
+        let report = Report {
+            targets: vec![TargetReport {
+                target: "orders".to_string(),
+                state: State::Ok,
+                summary: "PostgreSQL 17.0, runtime 42ms".to_string(),
+                runtime_ms: Some(42),
+                lag_seconds: None,
+                cert_expiry_days: Some(90),
+            }],
+            thresholds,
+        };
+        assert_eq!(report.state(), State::Ok);
+        assert_eq!(
+            report.to_string(),
+            "DBPULSE OK - orders: PostgreSQL 17.0, runtime 42ms | runtime=42ms;500;1000;0 cert_expiry_days=90;;"
+        );
+    }
+
+    #[test]
+    fn test_report_multiple_targets() {
+        let report = Report {
+            targets: vec![
+                TargetReport {
+                    target: "orders".to_string(),
+                    state: State::Ok,
+                    summary: "PostgreSQL 17.0, runtime 42ms".to_string(),
+                    runtime_ms: Some(42),
+                    lag_seconds: None,
+                    cert_expiry_days: None,
+                },
+                TargetReport {
+                    target: "o'brien".to_string(),
+                    state: State::Critical,
+                    summary: "connection refused".to_string(),
+                    runtime_ms: Some(3),
+                    lag_seconds: None,
+                    cert_expiry_days: None,
+                },
+            ],
+            thresholds: Thresholds::default(),
+        };
+        assert_eq!(report.state(), State::Critical);
+
+        let output = report.to_string();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "DBPULSE CRITICAL - 2 targets: 1 critical, 1 ok | 'orders runtime'=42ms;;;0 'o''brien runtime'=3ms;;;0",
+                "OK - orders: PostgreSQL 17.0, runtime 42ms",
+                "CRITICAL - o'brien: connection refused",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_without_targets_is_unknown() {
+        let report = Report {
+            targets: Vec::new(),
+            thresholds: Thresholds::default(),
+        };
+        assert_eq!(report.state(), State::Unknown);
+        assert_eq!(
+            report.to_string(),
+            "DBPULSE UNKNOWN - no targets configured"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_target_unsupported_driver() {
+        let config = TargetConfig {
+            dsn: "oracle://tcp(localhost:1)/db".to_string(),
+            ..TargetConfig::from_dsn("postgres://tcp(localhost:1)/db").unwrap()
+        };
+        let report = check_target(
+            &config,
+            &CertCache::new(std::time::Duration::ZERO),
+            &Thresholds::default(),
+        )
+        .await;
+        assert_eq!(report.state, State::Unknown);
+    }
+}
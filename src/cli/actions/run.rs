@@ -3,13 +3,10 @@ use super::Action;
 /// Execute the action's business logic by delegating to the appropriate module
 pub async fn execute(action: Action) -> anyhow::Result<()> {
     match action {
-        Action::Monitor {
-            dsn,
-            interval,
-            listen,
-            port,
-            range,
-            tls,
-        } => crate::pulse::start(dsn, interval, listen, port, range, tls).await,
+        Action::Monitor { config, source } => crate::pulse::start(config, source).await,
+        Action::Check { config, thresholds } => {
+            let state = crate::check::execute(config, thresholds).await;
+            std::process::exit(state.exit_code().into())
+        }
     }
 }
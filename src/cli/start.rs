@@ -1,4 +1,5 @@
 use super::{commands, dispatch};
+use crate::check;
 use anyhow::Result;
 
 /// Main orchestrator - Pure orchestration with no business logic
@@ -15,7 +16,17 @@ use anyhow::Result;
 /// Returns an error if any step in the flow fails
 pub async fn start() -> Result<()> {
     // 1. Parse: Extract CLI arguments
-    let matches = commands::new().get_matches();
+    // `dbpulse check` must exit with UNKNOWN (3) on invalid arguments, not 2
+    let is_check = std::env::args().nth(1).as_deref() == Some(check::COMMAND);
+    let matches = match commands::new().try_get_matches() {
+        Ok(matches) => matches,
+        Err(e) if is_check && e.use_stderr() => {
+            let message = e.render().to_string();
+            let first_line = message.lines().next().unwrap_or_default();
+            check::exit_unknown(first_line.trim_start_matches("error: "))
+        }
+        Err(e) => e.exit(),
+    };
 
     // 2. Extract Verbosity (optional - not used in dbpulse)
     // let verbosity = extract_verbosity(&matches);
@@ -24,7 +35,11 @@ pub async fn start() -> Result<()> {
     // telemetry::init(verbosity)?;
 
     // 4. Dispatch: Convert ArgMatches into typed Action enum
-    let action = dispatch::dispatch(&matches)?;
+    let action = match dispatch::dispatch(&matches) {
+        Ok(action) => action,
+        Err(e) if is_check => check::exit_unknown(format!("{e:#}")),
+        Err(e) => return Err(e),
+    };
 
     // 5. Execute: Run the action's business logic
     action.execute().await?;
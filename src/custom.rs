@@ -0,0 +1,271 @@
+//! User-defined SQL checks
+//!
+//! Every target may define named checks: a query returning a single scalar
+//! and an optional condition the value must satisfy. Checks run next to the
+//! read/write check of their target, each on its own interval, and are
+//! exported as `dbpulse_custom_check_value` and `dbpulse_custom_check_ok`.
+
+use crate::{
+    metrics::{CUSTOM_CHECK_ERRORS, CUSTOM_CHECK_OK, CUSTOM_CHECK_VALUE},
+    queries::{DeadlineExceeded, mysql, postgres, sqlite},
+    target::Target,
+};
+use anyhow::Result;
+use std::{fmt, str::FromStr, sync::Arc, time::Duration};
+use tokio::time::{self, Instant};
+
+/// Type of the scalar returned by a check query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueType {
+    /// `BIGINT`/`INT8`, e.g. `count(*)`
+    #[default]
+    Integer,
+    /// `DOUBLE PRECISION`/`DOUBLE`, cast `numeric`/`DECIMAL` results
+    Float,
+    /// `BOOLEAN`, exported as 1 or 0
+    Boolean,
+}
+
+impl FromStr for ValueType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "integer" | "int" => Ok(Self::Integer),
+            "float" | "double" => Ok(Self::Float),
+            "boolean" | "bool" => Ok(Self::Boolean),
+            _ => Err(format!(
+                "Invalid check type: {s} (expected integer, float or boolean)"
+            )),
+        }
+    }
+}
+
+/// Comparison between the value of a check and its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Comparison {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+        }
+    }
+
+    /// Whether `value <op> threshold` holds
+    #[must_use]
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Lt => value < threshold,
+            Self::Le => value <= threshold,
+            Self::Gt => value > threshold,
+            Self::Ge => value >= threshold,
+            Self::Eq => (value - threshold).abs() < f64::EPSILON,
+            Self::Ne => (value - threshold).abs() >= f64::EPSILON,
+        }
+    }
+}
+
+impl FromStr for Comparison {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "<" | "lt" => Ok(Self::Lt),
+            "<=" | "le" => Ok(Self::Le),
+            ">" | "gt" => Ok(Self::Gt),
+            ">=" | "ge" => Ok(Self::Ge),
+            "==" | "=" | "eq" => Ok(Self::Eq),
+            "!=" | "<>" | "ne" => Ok(Self::Ne),
+            _ => Err(format!(
+                "Invalid comparison: {s} (expected <, <=, >, >=, == or !=)"
+            )),
+        }
+    }
+}
+
+/// Condition the value of a check must satisfy to be ok
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Condition {
+    pub op: Comparison,
+    pub threshold: f64,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.op.as_str(), self.threshold)
+    }
+}
+
+/// A named SQL check of a target
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCheck {
+    /// Value of the `check` label
+    pub name: String,
+    pub sql: String,
+    pub value_type: ValueType,
+    /// Without a condition any value is ok, booleans must be true
+    pub condition: Option<Condition>,
+    pub interval: Duration,
+}
+
+impl CustomCheck {
+    /// Whether a value returned by the query satisfies the check
+    #[must_use]
+    pub fn is_ok(&self, value: f64) -> bool {
+        match (self.condition, self.value_type) {
+            (Some(condition), _) => condition.op.holds(value, condition.threshold),
+            (None, ValueType::Boolean) => value != 0.0,
+            (None, _) => true,
+        }
+    }
+}
+
+/// Convert an integer result to the value of a gauge
+#[allow(clippy::cast_precision_loss)]
+pub(crate) const fn integer_value(value: i64) -> f64 {
+    // integers beyond 2^53 lose precision, plenty for counts and ages
+    value as f64
+}
+
+/// Run the custom checks of a target forever, each on its own interval
+pub(crate) async fn run(target: Arc<Target>, database: &'static str) {
+    let start = Instant::now();
+    let mut due = vec![start; target.checks.len()];
+
+    loop {
+        let now = Instant::now();
+        for (check, next) in target.checks.iter().zip(due.iter_mut()) {
+            if *next <= now {
+                run_check(&target, database, check).await;
+                *next = now + check.interval;
+            }
+        }
+
+        let Some(next) = due.iter().min() else {
+            return;
+        };
+        time::sleep_until(*next).await;
+    }
+}
+
+async fn run_check(target: &Target, database: &str, check: &CustomCheck) {
+    let labels = [target.name.as_str(), database, check.name.as_str()];
+    let value = time::timeout(target.timeouts.iteration, query(target, database, check))
+        .await
+        .map_err(|_| DeadlineExceeded {
+            operation: "custom check",
+            limit: target.timeouts.iteration,
+        })
+        .map_err(anyhow::Error::from)
+        .and_then(|value| value);
+
+    match value {
+        Ok(value) => {
+            CUSTOM_CHECK_VALUE.with_label_values(&labels).set(value);
+            CUSTOM_CHECK_OK
+                .with_label_values(&labels)
+                .set(i64::from(check.is_ok(value)));
+        }
+        Err(error) => {
+            eprintln!("{}: check {} failed: {error:#}", target.name, check.name);
+            let _ = CUSTOM_CHECK_VALUE.remove_label_values(&labels);
+            CUSTOM_CHECK_OK.with_label_values(&labels).set(0);
+            CUSTOM_CHECK_ERRORS.with_label_values(&labels).inc();
+        }
+    }
+}
+
+async fn query(target: &Target, database: &str, check: &CustomCheck) -> Result<f64> {
+    match database {
+        "postgres" => postgres::custom_check_value(target, &check.sql, check.value_type).await,
+        "mysql" => mysql::custom_check_value(target, &check.sql, check.value_type).await,
+        "sqlite" => sqlite::custom_check_value(target, &check.sql, check.value_type).await,
+        _ => unreachable!("unsupported database label"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+
+    fn check(value_type: ValueType, condition: Option<Condition>) -> CustomCheck {
+        CustomCheck {
+            name: "pending_jobs".to_string(),
+            sql: "SELECT count(*) FROM jobs".to_string(),
+            value_type,
+            condition,
+            interval: Duration::from_mins(1),
+        }
+    }
+
+    #[test]
+    fn test_comparison_from_str() {
+        assert_eq!("<".parse::<Comparison>().unwrap(), Comparison::Lt);
+        assert_eq!("GE".parse::<Comparison>().unwrap(), Comparison::Ge);
+        assert_eq!("<>".parse::<Comparison>().unwrap(), Comparison::Ne);
+        assert!("~".parse::<Comparison>().is_err());
+    }
+
+    #[test]
+    fn test_comparison_holds() {
+        assert!(Comparison::Lt.holds(9_999.0, 10_000.0));
+        assert!(!Comparison::Lt.holds(10_000.0, 10_000.0));
+        assert!(Comparison::Le.holds(10_000.0, 10_000.0));
+        assert!(Comparison::Gt.holds(1.5, 1.0));
+        assert!(Comparison::Ge.holds(1.0, 1.0));
+        assert!(Comparison::Eq.holds(0.0, 0.0));
+        assert!(Comparison::Ne.holds(1.0, 0.0));
+    }
+
+    #[test]
+    fn test_value_type_from_str() {
+        assert_eq!("int".parse::<ValueType>().unwrap(), ValueType::Integer);
+        assert_eq!("Float".parse::<ValueType>().unwrap(), ValueType::Float);
+        assert_eq!("bool".parse::<ValueType>().unwrap(), ValueType::Boolean);
+        assert!("text".parse::<ValueType>().is_err());
+    }
+
+    #[test]
+    fn test_is_ok() {
+        let pending = check(
+            ValueType::Integer,
+            Some(Condition {
+                op: Comparison::Lt,
+                threshold: 10_000.0,
+            }),
+        );
+        assert!(pending.is_ok(42.0));
+        assert!(!pending.is_ok(10_000.0));
+
+        // booleans must be true unless a condition says otherwise
+        assert!(check(ValueType::Boolean, None).is_ok(1.0));
+        assert!(!check(ValueType::Boolean, None).is_ok(0.0));
+        // without a condition the value is only exported
+        assert!(check(ValueType::Float, None).is_ok(-3.5));
+    }
+
+    #[test]
+    fn test_condition_display() {
+        let condition = Condition {
+            op: Comparison::Ge,
+            threshold: 0.5,
+        };
+        assert_eq!(condition.to_string(), ">= 0.5");
+    }
+}
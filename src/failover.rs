@@ -0,0 +1,280 @@
+//! Failover, restart and role change detection
+//!
+//! Every successful check reports the host answering the target, its uptime
+//! and whether it is read-only. Comparing them with the previous check tells
+//! a failover (another host answers), a restart (the uptime went backwards)
+//! and a role flip (a primary became read-only or a replica was promoted).
+//! Each change is counted and logged as a JSON line next to the pulses.
+
+use crate::metrics::{FAILOVERS, LAST_FAILOVER, RESTARTS};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Role of the server answering a target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Primary,
+    /// A `PostgreSQL` standby or a `read_only` `MySQL` server
+    Replica,
+}
+
+impl Role {
+    #[must_use]
+    pub const fn from_read_only(read_only: bool) -> Self {
+        if read_only {
+            Self::Replica
+        } else {
+            Self::Primary
+        }
+    }
+}
+
+/// A change of the server behind a target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Change {
+    /// Another host answers the target
+    Failover { previous_host: String, host: String },
+    /// The same host answers with a different role
+    RoleChange { previous_role: Role, role: Role },
+    /// The uptime of the same host went backwards
+    Restart {
+        previous_uptime_seconds: i64,
+        uptime_seconds: i64,
+    },
+}
+
+impl Change {
+    /// Value of the `reason` label of `dbpulse_failovers_total`, `None` for restarts
+    #[must_use]
+    pub const fn failover_reason(&self) -> Option<&'static str> {
+        match self {
+            Self::Failover { .. } => Some("host_changed"),
+            Self::RoleChange {
+                role: Role::Primary,
+                ..
+            } => Some("promoted"),
+            Self::RoleChange {
+                role: Role::Replica,
+                ..
+            } => Some("demoted"),
+            Self::Restart { .. } => None,
+        }
+    }
+}
+
+/// Identity of the server seen by the previous successful check
+#[derive(Debug, Default)]
+pub(crate) struct Tracker {
+    host: Option<String>,
+    uptime: Option<i64>,
+    role: Option<Role>,
+}
+
+impl Tracker {
+    /// Compare a successful check with the previous one
+    ///
+    /// The first check of a loop only records the baseline. A failover hides
+    /// the role and uptime changes it implies, the new host is a different
+    /// server.
+    pub(crate) fn observe(
+        &mut self,
+        host: Option<&str>,
+        uptime: Option<i64>,
+        read_only: bool,
+    ) -> Vec<Change> {
+        let role = Role::from_read_only(read_only);
+        let mut changes = Vec::new();
+
+        let failover = match (self.host.as_deref(), host) {
+            (Some(previous), Some(current)) if previous != current => {
+                changes.push(Change::Failover {
+                    previous_host: previous.to_string(),
+                    host: current.to_string(),
+                });
+                true
+            }
+            _ => false,
+        };
+
+        if !failover {
+            if let Some(previous_role) = self.role
+                && previous_role != role
+            {
+                changes.push(Change::RoleChange {
+                    previous_role,
+                    role,
+                });
+            }
+            if let (Some(previous), Some(current)) = (self.uptime, uptime)
+                && current < previous
+            {
+                changes.push(Change::Restart {
+                    previous_uptime_seconds: previous,
+                    uptime_seconds: current,
+                });
+            }
+        }
+
+        if let Some(host) = host {
+            self.host = Some(host.to_string());
+        }
+        if uptime.is_some() {
+            self.uptime = uptime;
+        }
+        self.role = Some(role);
+
+        changes
+    }
+}
+
+/// Structured log line of a change
+#[derive(Serialize)]
+struct Event<'a> {
+    target: &'a str,
+    database: &'a str,
+    time: DateTime<Utc>,
+    #[serde(flatten)]
+    change: &'a Change,
+}
+
+/// Count and log the changes detected by a check
+pub(crate) fn record(target: &str, database: &str, now: DateTime<Utc>, changes: &[Change]) {
+    for change in changes {
+        if let Some(reason) = change.failover_reason() {
+            FAILOVERS
+                .with_label_values(&[target, database, reason])
+                .inc();
+            LAST_FAILOVER
+                .with_label_values(&[target, database])
+                .set(now.timestamp());
+        } else {
+            RESTARTS.with_label_values(&[target, database]).inc();
+        }
+
+        let event = Event {
+            target,
+            database,
+            time: now,
+            change,
+        };
+        if let Ok(serialized) = serde_json::to_string(&event) {
+            println!("{serialized}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+
+    #[test]
+    fn test_first_check_is_baseline() {
+        let mut tracker = Tracker::default();
+        assert!(tracker.observe(Some("db-1"), Some(100), true).is_empty());
+        assert!(tracker.observe(Some("db-1"), Some(130), true).is_empty());
+    }
+
+    #[test]
+    fn test_failover() {
+        let mut tracker = Tracker::default();
+        tracker.observe(Some("db-1"), Some(1_000), false);
+
+        // the new primary was a replica with a shorter uptime, only the failover counts
+        let changes = tracker.observe(Some("db-2"), Some(50), false);
+        assert_eq!(
+            changes,
+            vec![Change::Failover {
+                previous_host: "db-1".to_string(),
+                host: "db-2".to_string(),
+            }]
+        );
+        assert_eq!(
+            changes.first().unwrap().failover_reason(),
+            Some("host_changed")
+        );
+
+        // an unknown host is not a failover
+        assert!(tracker.observe(None, Some(80), false).is_empty());
+        assert!(tracker.observe(Some("db-2"), Some(110), false).is_empty());
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut tracker = Tracker::default();
+        tracker.observe(Some("db-1"), Some(86_400), false);
+        assert_eq!(
+            tracker.observe(Some("db-1"), Some(5), false),
+            vec![Change::Restart {
+                previous_uptime_seconds: 86_400,
+                uptime_seconds: 5,
+            }]
+        );
+        assert!(tracker.observe(Some("db-1"), Some(35), false).is_empty());
+    }
+
+    #[test]
+    fn test_role_change() {
+        let mut tracker = Tracker::default();
+        tracker.observe(Some("db-1"), Some(100), true);
+
+        let promoted = tracker.observe(Some("db-1"), Some(130), false);
+        assert_eq!(
+            promoted,
+            vec![Change::RoleChange {
+                previous_role: Role::Replica,
+                role: Role::Primary,
+            }]
+        );
+        assert_eq!(
+            promoted.first().unwrap().failover_reason(),
+            Some("promoted")
+        );
+
+        let demoted = tracker.observe(Some("db-1"), Some(160), true);
+        assert_eq!(demoted.first().unwrap().failover_reason(), Some("demoted"));
+    }
+
+    #[test]
+    fn test_record_metrics_and_event() {
+        let now = Utc::now();
+        let changes = [
+            Change::Failover {
+                previous_host: "db-1".to_string(),
+                host: "db-2".to_string(),
+            },
+            Change::Restart {
+                previous_uptime_seconds: 100,
+                uptime_seconds: 1,
+            },
+        ];
+        record("failover-db", "postgres", now, &changes);
+
+        let labels = ["failover-db", "postgres"];
+        assert_eq!(
+            FAILOVERS
+                .with_label_values(&["failover-db", "postgres", "host_changed"])
+                .get(),
+            1
+        );
+        assert_eq!(RESTARTS.with_label_values(&labels).get(), 1);
+        assert_eq!(
+            LAST_FAILOVER.with_label_values(&labels).get(),
+            now.timestamp()
+        );
+
+        let event = Event {
+            target: "failover-db",
+            database: "postgres",
+            time: now,
+            change: changes.first().unwrap(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json.pointer("/event").unwrap(), "failover");
+        assert_eq!(json.pointer("/previous_host").unwrap(), "db-1");
+        assert_eq!(json.pointer("/target").unwrap(), "failover-db");
+    }
+}
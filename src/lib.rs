@@ -1,5 +1,13 @@
+pub mod alert;
+pub mod check;
 pub mod cli;
+pub mod config;
+pub mod custom;
+pub mod failover;
 pub mod metrics;
 pub mod pulse;
 pub mod queries;
+pub mod reload;
+pub mod status;
+pub mod target;
 pub mod tls;
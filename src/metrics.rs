@@ -1,11 +1,16 @@
 use prometheus::{
-    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
-    IntGaugeVec, Registry, opts, register_histogram_vec_with_registry,
-    register_histogram_with_registry, register_int_counter_vec_with_registry,
-    register_int_counter_with_registry, register_int_gauge_vec_with_registry,
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Registry,
+    core::{Atomic, AtomicF64, AtomicI64, Collector, Desc, GenericGaugeVec},
+    opts,
+    proto::MetricFamily,
+    register_gauge_vec_with_registry, register_histogram_vec_with_registry,
+    register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry,
     register_int_gauge_with_registry,
 };
-use std::sync::LazyLock;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::{Arc, LazyLock, Mutex, PoisonError},
+};
 
 pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
 
@@ -28,14 +33,19 @@ where
     }
 }
 
-pub static PULSE: LazyLock<IntGauge> = LazyLock::new(|| {
-    register_int_gauge_with_registry!("dbpulse_pulse", "1 ok, 0 error", &REGISTRY)
-        .or_exit("metric can be created")
+pub static PULSE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!("dbpulse_pulse", "1 ok, 0 error"),
+        &["target"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
 });
 
-pub static RUNTIME: LazyLock<Histogram> = LazyLock::new(|| {
-    register_histogram_with_registry!(
+pub static RUNTIME: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec_with_registry!(
         HistogramOpts::new("dbpulse_runtime", "pulse latency in seconds"),
+        &["target"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -48,7 +58,7 @@ pub static TLS_HANDSHAKE_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
             "dbpulse_tls_handshake_duration_seconds",
             "TLS handshake duration in seconds"
         ),
-        &["database"],
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -60,22 +70,24 @@ pub static TLS_CONNECTION_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
             "dbpulse_tls_connection_errors_total",
             "Total TLS connection errors by type"
         ),
-        &["database", "error_type"],
+        &["target", "database", "error_type"],
         &REGISTRY
     )
     .or_exit("metric can be created")
 });
 
-pub static TLS_INFO: LazyLock<IntGaugeVec> = LazyLock::new(|| {
-    register_int_gauge_vec_with_registry!(
-        opts!(
-            "dbpulse_tls_info",
-            "TLS connection info (version, cipher) - value is always 1"
-        ),
-        &["database", "version", "cipher"],
-        &REGISTRY
+pub static TLS_INFO: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_tls_info",
+                "TLS connection info (version, cipher) - value is always 1"
+            ),
+            &["target", "database", "version", "cipher"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
     )
-    .or_exit("metric can be created")
 });
 
 pub static TLS_CERT_EXPIRY_DAYS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
@@ -84,7 +96,7 @@ pub static TLS_CERT_EXPIRY_DAYS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
             "dbpulse_tls_cert_expiry_days",
             "Days until TLS certificate expiration (negative if expired)"
         ),
-        &["database"],
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -96,43 +108,83 @@ pub static TLS_CERT_PROBE_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
             "dbpulse_tls_cert_probe_errors_total",
             "Total certificate probe errors by type (connection, handshake, parse, timeout)"
         ),
-        &["database", "error_type"],
+        &["target", "database", "error_type"],
         &REGISTRY
     )
     .or_exit("metric can be created")
 });
 
-pub static DATABASE_VERSION_INFO: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+pub static DATABASE_VERSION_INFO: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_database_version_info",
+                "Database server version and flavor info (value is always 1)"
+            ),
+            &["target", "database", "version", "flavor"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static DATABASE_HOST_INFO: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_database_host_info",
+                "Database host currently serving the connection (value is always 1)"
+            ),
+            &["target", "database", "host"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static DATABASE_UPTIME_SECONDS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
     register_int_gauge_vec_with_registry!(
         opts!(
-            "dbpulse_database_version_info",
-            "Database server version info (value is always 1)"
+            "dbpulse_database_uptime_seconds",
+            "How long (in seconds) the database has been up"
         ),
-        &["database", "version"],
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
 });
 
-pub static DATABASE_HOST_INFO: LazyLock<IntGaugeVec> = LazyLock::new(|| {
-    register_int_gauge_vec_with_registry!(
+pub static FAILOVERS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
         opts!(
-            "dbpulse_database_host_info",
-            "Database host currently serving the connection (value is always 1)"
+            "dbpulse_failovers_total",
+            "Total failovers: another host answers the target or its role changed"
         ),
-        &["database", "host"],
+        &["target", "database", "reason"],
         &REGISTRY
     )
     .or_exit("metric can be created")
 });
 
-pub static DATABASE_UPTIME_SECONDS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+pub static LAST_FAILOVER: LazyLock<IntGaugeVec> = LazyLock::new(|| {
     register_int_gauge_vec_with_registry!(
         opts!(
-            "dbpulse_database_uptime_seconds",
-            "How long (in seconds) the database has been up"
+            "dbpulse_last_failover_timestamp_seconds",
+            "Unix timestamp of the last detected failover"
         ),
-        &["database"],
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static RESTARTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_restarts_total",
+            "Total database restarts detected by a decreasing uptime"
+        ),
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -144,7 +196,7 @@ pub static LAST_RUNTIME_MS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
             "dbpulse_runtime_last_milliseconds",
             "Runtime of the most recent health check iteration in milliseconds"
         ),
-        &["database"],
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -154,7 +206,19 @@ pub static LAST_RUNTIME_MS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
 pub static DB_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
     register_int_counter_vec_with_registry!(
         opts!("dbpulse_errors_total", "Total database errors by type"),
-        &["database", "error_type"],
+        &["target", "database", "error_type"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static DNS_RESOLUTION_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_dns_resolution_failures_total",
+            "Total lookups of the target host name that failed or returned no address"
+        ),
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -166,18 +230,43 @@ pub static OPERATION_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
             "dbpulse_operation_duration_seconds",
             "Duration of specific database operations"
         ),
-        &["database", "operation"],
+        &["target", "database", "operation"],
         &REGISTRY
     )
     .or_exit("metric can be created")
 });
 
-pub static CONNECTION_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
-    register_histogram_with_registry!(
+pub static CONNECTION_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec_with_registry!(
         HistogramOpts::new(
             "dbpulse_connection_duration_seconds",
             "Time connection is held open"
         ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static CONNECTION_AGE_SECONDS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_connection_age_seconds",
+            "Age of the connection used by the last check (persistent connection mode)"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static RECONNECTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_reconnects_total",
+            "Total connections replaced in persistent connection mode by reason (error, ping, role)"
+        ),
+        &["target", "database", "reason"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -190,7 +279,7 @@ pub static ROWS_AFFECTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
             "dbpulse_rows_affected_total",
             "Total rows affected by operations"
         ),
-        &["database", "operation"],
+        &["target", "database", "operation"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -199,7 +288,7 @@ pub static ROWS_AFFECTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
 pub static ITERATIONS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
     register_int_counter_vec_with_registry!(
         opts!("dbpulse_iterations_total", "Total monitoring iterations"),
-        &["database", "status"],
+        &["target", "database", "status"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -211,40 +300,45 @@ pub static LAST_SUCCESS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
             "dbpulse_last_success_timestamp_seconds",
             "Unix timestamp of last successful check"
         ),
-        &["database"],
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
 });
 
 // Medium Priority Metrics
-pub static TABLE_SIZE_BYTES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
-    register_int_gauge_vec_with_registry!(
-        opts!(
-            "dbpulse_table_size_bytes",
-            "Approximate table size in bytes"
-        ),
-        &["database", "table"],
-        &REGISTRY
+pub static TABLE_SIZE_BYTES: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_table_size_bytes",
+                "Approximate table size in bytes"
+            ),
+            &["target", "database", "table"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
     )
-    .or_exit("metric can be created")
 });
 
-pub static TABLE_ROWS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
-    register_int_gauge_vec_with_registry!(
-        opts!("dbpulse_table_rows", "Approximate row count"),
-        &["database", "table"],
-        &REGISTRY
+pub static TABLE_ROWS: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!("dbpulse_table_rows", "Approximate row count"),
+            &["target", "database", "table"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
     )
-    .or_exit("metric can be created")
 });
 
-pub static PANICS_RECOVERED: LazyLock<IntCounter> = LazyLock::new(|| {
-    register_int_counter_with_registry!(
+pub static PANICS_RECOVERED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
         opts!(
             "dbpulse_panics_recovered_total",
             "Total panics recovered from"
         ),
+        &["target"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -256,7 +350,7 @@ pub static DB_READONLY: LazyLock<IntGaugeVec> = LazyLock::new(|| {
             "dbpulse_database_readonly",
             "1 if database is in read-only mode"
         ),
-        &["database"],
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -269,7 +363,526 @@ pub static REPLICATION_LAG: LazyLock<HistogramVec> = LazyLock::new(|| {
             "dbpulse_replication_lag_seconds",
             "Replication lag in seconds (for replicas)"
         ),
-        &["database"],
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static REPLICA_VISIBILITY_SECONDS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec_with_registry!(
+        HistogramOpts::new(
+            "dbpulse_replica_visibility_seconds",
+            "Time from the commit on the primary until the row was readable on a replica"
+        )
+        .buckets(vec![
+            0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0
+        ]),
+        &["target", "database", "replica"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static REPLICA_VISIBILITY_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_replica_visibility_failures_total",
+            "Total written rows not readable on a replica, by reason (timeout, error)"
+        ),
+        &["target", "database", "replica", "reason"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+/// Gauges labelled by names the server reports, e.g. standbys or replication slots
+///
+/// A check reports all series of its target at once; series missing from the
+/// latest report (a disconnected standby, a dropped slot) are removed.
+pub struct ServerSeries<P: Atomic> {
+    pub vec: GenericGaugeVec<P>,
+    reported: Mutex<HashMap<String, HashSet<Vec<String>>>>,
+}
+
+impl<P: Atomic> ServerSeries<P> {
+    fn new(vec: GenericGaugeVec<P>) -> Self {
+        Self {
+            vec,
+            reported: Mutex::default(),
+        }
+    }
+
+    /// Replace the series of a target with the label values of the latest report
+    pub fn replace(&self, target: &str, current: HashSet<Vec<String>>) {
+        let mut reported = self.reported.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(previous) = reported.get(target) {
+            for labels in previous.difference(&current) {
+                let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+                let _ = self.vec.remove_label_values(&labels);
+            }
+        }
+        reported.insert(target.to_string(), current);
+    }
+
+    /// Drop every series of a target
+    pub fn remove_target(&self, target: &str) {
+        self.replace(target, HashSet::new());
+    }
+
+    /// Set the one series a target reports, removing the one it replaces
+    pub fn set_single(&self, target: &str, labels: &[&str], value: P::T) {
+        self.vec.with_label_values(labels).set(value);
+        self.replace(
+            target,
+            HashSet::from([labels.iter().map(ToString::to_string).collect()]),
+        );
+    }
+}
+
+pub static STANDBY_LAG_SECONDS: LazyLock<ServerSeries<AtomicF64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_standby_lag_seconds",
+                "Replication lag of a standby seen from the primary, by stage (write, flush, replay)"
+            ),
+            &["target", "database", "application_name", "stage"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static STANDBY_LAG_BYTES: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_standby_lag_bytes",
+                "WAL bytes between the current LSN of the primary and a standby, by stage (sent, replay)"
+            ),
+            &["target", "database", "application_name", "stage"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static REPLICATION_SLOT_RETAINED_BYTES: LazyLock<ServerSeries<AtomicI64>> =
+    LazyLock::new(|| {
+        ServerSeries::new(
+            register_int_gauge_vec_with_registry!(
+                opts!(
+                    "dbpulse_replication_slot_retained_bytes",
+                    "WAL bytes retained on the primary by a replication slot"
+                ),
+                &["target", "database", "slot", "slot_type"],
+                &REGISTRY
+            )
+            .or_exit("metric can be created"),
+        )
+    });
+
+pub static REPLICATION_SLOT_ACTIVE: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_replication_slot_active",
+                "Whether a replication slot is in use by a consumer (1) or inactive (0)"
+            ),
+            &["target", "database", "slot", "slot_type"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static REPLICA_THREAD_RUNNING: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_replica_thread_running",
+                "Whether a replication thread of a channel is running (1) or not (0), by thread (io, sql)"
+            ),
+            &["target", "database", "channel", "thread"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static REPLICA_LAST_ERRNO: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_replica_last_errno",
+                "Number of the last error of a replication thread of a channel, 0 if none"
+            ),
+            &["target", "database", "channel", "thread"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static REPLICA_RELAY_LOG_SPACE_BYTES: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_replica_relay_log_space_bytes",
+                "Total size of the relay logs of a replication channel"
+            ),
+            &["target", "database", "channel"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static REPLICA_GTID_GAP: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_replica_gtid_gap_transactions",
+                "Transactions retrieved by a replication channel but not executed yet"
+            ),
+            &["target", "database", "channel"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+// Cluster metrics (Galera, Group Replication)
+pub static GALERA_CLUSTER_SIZE: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_galera_cluster_size",
+                "Number of nodes in the Galera component of the node (wsrep_cluster_size)"
+            ),
+            &["target", "database"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static GALERA_STATE_INFO: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_galera_state_info",
+                "Galera node state (wsrep_local_state_comment) and component status (wsrep_cluster_status), value is always 1"
+            ),
+            &["target", "database", "local_state", "cluster_status"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static GALERA_FLOW_CONTROL_PAUSED: LazyLock<ServerSeries<AtomicF64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_galera_flow_control_paused_ratio",
+                "Fraction of time replication was paused by flow control (wsrep_flow_control_paused)"
+            ),
+            &["target", "database"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static GROUP_REPLICATION_MEMBER: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_group_replication_member_info",
+                "Group Replication members seen by the node with their state and role, value is always 1"
+            ),
+            &["target", "database", "member", "state", "role"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+/// States of `dbpulse_cluster_nodes`, `draining` includes decommissioning nodes
+pub const CLUSTER_NODE_STATES: [&str; 3] = ["live", "draining", "unavailable"];
+
+pub static CLUSTER_NODES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_cluster_nodes",
+            "Nodes of a CockroachDB or YugabyteDB cluster by state (live, draining, unavailable)"
+        ),
+        &["target", "database", "state"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static PGBOUNCER_CLIENTS: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_pgbouncer_clients",
+                "Client connections of a PgBouncer pool by state (active, waiting)"
+            ),
+            &["target", "database", "pool", "user", "state"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PGBOUNCER_SERVERS: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_pgbouncer_servers",
+                "Server connections of a PgBouncer pool by state (active, idle, used, tested, login)"
+            ),
+            &["target", "database", "pool", "user", "state"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PGBOUNCER_MAX_WAIT_SECONDS: LazyLock<ServerSeries<AtomicF64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_pgbouncer_max_wait_seconds",
+                "How long the oldest waiting client of a PgBouncer pool has waited"
+            ),
+            &["target", "database", "pool", "user"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PGBOUNCER_TRANSACTIONS: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_pgbouncer_transactions",
+                "Transactions pooled by PgBouncer for a database since it started (total_xact_count)"
+            ),
+            &["target", "database", "pool"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PGBOUNCER_QUERIES: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_pgbouncer_queries",
+                "Queries pooled by PgBouncer for a database since it started (total_query_count)"
+            ),
+            &["target", "database", "pool"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PGBOUNCER_WAIT_SECONDS: LazyLock<ServerSeries<AtomicF64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_pgbouncer_wait_seconds",
+                "Time clients of a database waited for a server connection since PgBouncer started (total_wait_time)"
+            ),
+            &["target", "database", "pool"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PROXYSQL_BACKEND_INFO: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_proxysql_backend_info",
+                "ProxySQL backends of a hostgroup with their status (ONLINE, SHUNNED, OFFLINE_SOFT, OFFLINE_HARD), value is always 1"
+            ),
+            &["target", "database", "hostgroup", "backend", "status"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PROXYSQL_BACKEND_CONNECTIONS: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_proxysql_backend_connections",
+                "Connections of ProxySQL to a backend by state (used, free)"
+            ),
+            &["target", "database", "hostgroup", "backend", "state"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PROXYSQL_BACKEND_CONNECTION_ERRORS: LazyLock<ServerSeries<AtomicI64>> =
+    LazyLock::new(|| {
+        ServerSeries::new(
+            register_int_gauge_vec_with_registry!(
+                opts!(
+                    "dbpulse_proxysql_backend_connection_errors",
+                    "Failed connections of ProxySQL to a backend since it started (ConnERR)"
+                ),
+                &["target", "database", "hostgroup", "backend"],
+                &REGISTRY
+            )
+            .or_exit("metric can be created"),
+        )
+    });
+
+pub static PROXYSQL_BACKEND_QUERIES: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_proxysql_backend_queries",
+                "Queries ProxySQL sent to a backend since it started (Queries)"
+            ),
+            &["target", "database", "hostgroup", "backend"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static PROXYSQL_BACKEND_LATENCY_SECONDS: LazyLock<ServerSeries<AtomicF64>> =
+    LazyLock::new(|| {
+        ServerSeries::new(
+            register_gauge_vec_with_registry!(
+                opts!(
+                    "dbpulse_proxysql_backend_latency_seconds",
+                    "Ping latency of a backend measured by ProxySQL (Latency_us)"
+                ),
+                &["target", "database", "hostgroup", "backend"],
+                &REGISTRY
+            )
+            .or_exit("metric can be created"),
+        )
+    });
+
+pub static SQLITE_WAL_SIZE_BYTES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_sqlite_wal_size_bytes",
+            "Size of the write-ahead log of an SQLite database, 0 when there is none"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static SQLITE_JOURNAL_MODE: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_sqlite_journal_mode_info",
+                "Journal mode of an SQLite database (wal, delete, ...), value is always 1"
+            ),
+            &["target", "database", "mode"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+/// Checks of `dbpulse_sqlite_integrity_ok`
+pub const SQLITE_INTEGRITY_CHECKS: [&str; 2] = ["quick_check", "integrity_check"];
+
+pub static SQLITE_INTEGRITY_OK: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_sqlite_integrity_ok",
+            "1 if the last PRAGMA quick_check or integrity_check of an SQLite database returned ok, 0 otherwise"
+        ),
+        &["target", "database", "check"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static ADDRESS_UP: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_address_up",
+                "1 if an address the target host resolves to accepted a connection and served a read of the table, 0 otherwise"
+            ),
+            &["target", "database", "address"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static ADDRESS_CHECK_SECONDS: LazyLock<ServerSeries<AtomicF64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_address_check_duration_seconds",
+                "Time to connect to an address the target host resolves to and ping it"
+            ),
+            &["target", "database", "address"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static ADDRESS_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_address_errors_total",
+            "Total failed probes of an address the target host resolves to, by type"
+        ),
+        &["target", "database", "address", "error_type"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static SELECTED_HOST: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_selected_host_info",
+                "Host of a multi-host DSN the last check connected to, value is always 1"
+            ),
+            &["target", "database", "host"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static UNREACHABLE_HOSTS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_unreachable_hosts",
+            "Hosts of a multi-host DSN the last check could not connect to"
+        ),
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -279,9 +892,110 @@ pub static BLOCKING_QUERIES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
     register_int_gauge_vec_with_registry!(
         opts!(
             "dbpulse_blocking_queries",
-            "Number of queries currently blocking others"
+            "Number of sessions waiting for a lock held by another session"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static OLDEST_BLOCKED_QUERY: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_oldest_blocked_query_seconds",
+            "How long the oldest query waiting for a lock has been waiting, 0 if none"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static OLDEST_TRANSACTION: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_oldest_transaction_seconds",
+            "Age of the oldest open transaction on the server, 0 if none"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static LOCK_BLOCKERS: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_lock_blocker_sessions",
+                "Sessions waiting for locks held by the top blockers, by blocker user and client"
+            ),
+            &["target", "database", "user", "client"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static CONNECTIONS_CURRENT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_connections_current",
+            "Client connections currently open on the server"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static CONNECTIONS_MAX: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_connections_max",
+            "Maximum number of connections the server accepts (max_connections)"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static CONNECTIONS_MAX_USED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_connections_max_used",
+            "Highest number of simultaneous connections since the server started (MySQL Max_used_connections)"
         ),
-        &["database"],
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+/// Values of the `state` label of `dbpulse_connections_by_state`
+pub const CONNECTION_STATES: [&str; 3] = ["active", "idle", "idle_in_transaction"];
+
+pub static CONNECTIONS_BY_STATE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_connections_by_state",
+            "Client connections by state (active, idle, idle_in_transaction)"
+        ),
+        &["target", "database", "state"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static LONG_IDLE_IN_TRANSACTION: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_long_idle_in_transaction_sessions",
+            "Sessions idle in an open transaction for longer than the idle_in_transaction threshold"
+        ),
+        &["target", "database"],
         &REGISTRY
     )
     .or_exit("metric can be created")
@@ -293,12 +1007,488 @@ pub static DATABASE_SIZE_BYTES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
             "dbpulse_database_size_bytes",
             "Total database size in bytes"
         ),
-        &["database"],
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static POSTGRES_XID_AGE: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_postgres_xid_age",
+                "Transactions since the oldest unfrozen transaction ID of a database, age(datfrozenxid)"
+            ),
+            &["target", "database", "datname"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static POSTGRES_MULTIXACT_AGE: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_postgres_multixact_age",
+                "MultiXacts since the oldest unfrozen MultiXact ID of a database, mxid_age(datminmxid)"
+            ),
+            &["target", "database", "datname"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static POSTGRES_XID_WRAPAROUND_PERCENT: LazyLock<ServerSeries<AtomicF64>> = LazyLock::new(
+    || {
+        ServerSeries::new(
+            register_gauge_vec_with_registry!(
+                opts!(
+                    "dbpulse_postgres_xid_wraparound_percent",
+                    "XID age of a database as a percentage of autovacuum_freeze_max_age, anti-wraparound vacuums start at 100"
+                ),
+                &["target", "database", "datname"],
+                &REGISTRY
+            )
+            .or_exit("metric can be created"),
+        )
+    },
+);
+
+pub static POSTGRES_AUTOVACUUM_WORKERS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_postgres_autovacuum_workers",
+            "Autovacuum workers currently running"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static POSTGRES_DEAD_TUPLES: LazyLock<ServerSeries<AtomicI64>> = LazyLock::new(|| {
+    ServerSeries::new(
+        register_int_gauge_vec_with_registry!(
+            opts!(
+                "dbpulse_postgres_dead_tuples",
+                "Estimated dead tuples of the tables of the monitored database with the most of them"
+            ),
+            &["target", "database", "table"],
+            &REGISTRY
+        )
+        .or_exit("metric can be created"),
+    )
+});
+
+pub static INNODB_BUFFER_POOL_HIT_RATIO: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_buffer_pool_hit_ratio",
+            "Fraction of InnoDB logical reads served from the buffer pool since the server started"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_BUFFER_POOL_PAGES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_buffer_pool_pages",
+            "Pages in the InnoDB buffer pool (Innodb_buffer_pool_pages_total)"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_BUFFER_POOL_DIRTY_PAGES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_buffer_pool_dirty_pages",
+            "Modified pages of the InnoDB buffer pool not flushed yet (Innodb_buffer_pool_pages_dirty)"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_HISTORY_LIST_LENGTH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_history_list_length",
+            "Undo log entries InnoDB has not purged yet, grows with long running transactions"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_CHECKPOINT_AGE_BYTES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_checkpoint_age_bytes",
+            "Redo log bytes written since the last InnoDB checkpoint"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_LOG_CAPACITY_BYTES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_log_capacity_bytes",
+            "Size of the InnoDB redo log, the checkpoint age cannot exceed it"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_DEADLOCKS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_deadlocks",
+            "InnoDB deadlocks since the server started"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_ROW_LOCK_WAITS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_row_lock_waits",
+            "InnoDB row lock waits since the server started (Innodb_row_lock_waits)"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static INNODB_ROW_LOCK_CURRENT_WAITS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_innodb_row_lock_current_waits",
+            "InnoDB row lock waits in progress (Innodb_row_lock_current_waits)"
+        ),
+        &["target", "database"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+// Custom check metrics
+pub static CUSTOM_CHECK_VALUE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_custom_check_value",
+            "Value returned by the last run of a custom check"
+        ),
+        &["target", "database", "check"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static CUSTOM_CHECK_OK: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec_with_registry!(
+        opts!(
+            "dbpulse_custom_check_ok",
+            "1 if the last run of a custom check met its condition, 0 otherwise"
+        ),
+        &["target", "database", "check"],
         &REGISTRY
     )
     .or_exit("metric can be created")
 });
 
+pub static CUSTOM_CHECK_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_custom_check_errors_total",
+            "Total custom check runs whose query failed"
+        ),
+        &["target", "database", "check"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+// Alert metrics
+pub static WEBHOOK_DELIVERY_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_webhook_delivery_failures_total",
+            "Total alerts that could not be delivered to a webhook after all retries"
+        ),
+        &["webhook"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+// Configuration reload metrics
+pub static CONFIG_RELOADS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec_with_registry!(
+        opts!(
+            "dbpulse_config_reloads_total",
+            "Total configuration reloads by result (success, failure)"
+        ),
+        &["result"],
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static CONFIG_LAST_RELOAD_SUCCESSFUL: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge_with_registry!(
+        opts!(
+            "dbpulse_config_last_reload_successful",
+            "1 if the last configuration reload succeeded, 0 if it was rejected"
+        ),
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+pub static CONFIG_LAST_RELOAD_SUCCESS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge_with_registry!(
+        opts!(
+            "dbpulse_config_last_reload_success_timestamp_seconds",
+            "Unix timestamp of the last successfully applied configuration"
+        ),
+        &REGISTRY
+    )
+    .or_exit("metric can be created")
+});
+
+/// Drop the gauges of a target that is no longer monitored
+///
+/// Counters and histograms are kept so rates stay correct across reloads.
+pub fn remove_target_gauges(target: &str, database: &str) {
+    let _ = PULSE.remove_label_values(&[target]);
+    for gauge in [
+        &DATABASE_UPTIME_SECONDS,
+        &LAST_RUNTIME_MS,
+        &LAST_SUCCESS,
+        &DB_READONLY,
+        &TLS_CERT_EXPIRY_DAYS,
+        &BLOCKING_QUERIES,
+        &CONNECTIONS_CURRENT,
+        &CONNECTIONS_MAX,
+        &CONNECTIONS_MAX_USED,
+        &LONG_IDLE_IN_TRANSACTION,
+        &DATABASE_SIZE_BYTES,
+        &POSTGRES_AUTOVACUUM_WORKERS,
+        &UNREACHABLE_HOSTS,
+        &INNODB_BUFFER_POOL_PAGES,
+        &INNODB_BUFFER_POOL_DIRTY_PAGES,
+        &INNODB_HISTORY_LIST_LENGTH,
+        &INNODB_CHECKPOINT_AGE_BYTES,
+        &INNODB_LOG_CAPACITY_BYTES,
+        &INNODB_DEADLOCKS,
+        &INNODB_ROW_LOCK_WAITS,
+        &INNODB_ROW_LOCK_CURRENT_WAITS,
+        &CONNECTION_AGE_SECONDS,
+        &LAST_FAILOVER,
+        &SQLITE_WAL_SIZE_BYTES,
+    ] {
+        let _ = gauge.remove_label_values(&[target, database]);
+    }
+    for state in CONNECTION_STATES {
+        let _ = CONNECTIONS_BY_STATE.remove_label_values(&[target, database, state]);
+    }
+    for state in CLUSTER_NODE_STATES {
+        let _ = CLUSTER_NODES.remove_label_values(&[target, database, state]);
+    }
+    for check in SQLITE_INTEGRITY_CHECKS {
+        let _ = SQLITE_INTEGRITY_OK.remove_label_values(&[target, database, check]);
+    }
+    for gauge in [
+        &OLDEST_BLOCKED_QUERY,
+        &OLDEST_TRANSACTION,
+        &INNODB_BUFFER_POOL_HIT_RATIO,
+    ] {
+        let _ = gauge.remove_label_values(&[target, database]);
+    }
+    for series in [
+        &TLS_INFO,
+        &DATABASE_VERSION_INFO,
+        &DATABASE_HOST_INFO,
+        &TABLE_SIZE_BYTES,
+        &TABLE_ROWS,
+    ] {
+        series.remove_target(target);
+    }
+    LOCK_BLOCKERS.remove_target(target);
+    SELECTED_HOST.remove_target(target);
+    ADDRESS_UP.remove_target(target);
+    SQLITE_JOURNAL_MODE.remove_target(target);
+    ADDRESS_CHECK_SECONDS.remove_target(target);
+    for series in [
+        &POSTGRES_XID_AGE,
+        &POSTGRES_MULTIXACT_AGE,
+        &POSTGRES_DEAD_TUPLES,
+    ] {
+        series.remove_target(target);
+    }
+    POSTGRES_XID_WRAPAROUND_PERCENT.remove_target(target);
+    remove_pooler_series(target);
+    remove_replication_series(target);
+}
+
+/// Drop the pool and backend series of a `PgBouncer` or `ProxySQL` target
+fn remove_pooler_series(target: &str) {
+    for series in [
+        &PGBOUNCER_CLIENTS,
+        &PGBOUNCER_SERVERS,
+        &PGBOUNCER_TRANSACTIONS,
+        &PGBOUNCER_QUERIES,
+        &PROXYSQL_BACKEND_INFO,
+        &PROXYSQL_BACKEND_CONNECTIONS,
+        &PROXYSQL_BACKEND_CONNECTION_ERRORS,
+        &PROXYSQL_BACKEND_QUERIES,
+    ] {
+        series.remove_target(target);
+    }
+    for series in [
+        &PGBOUNCER_MAX_WAIT_SECONDS,
+        &PGBOUNCER_WAIT_SECONDS,
+        &PROXYSQL_BACKEND_LATENCY_SECONDS,
+    ] {
+        series.remove_target(target);
+    }
+}
+
+/// Drop the replication and cluster series a target reported
+pub fn remove_replication_series(target: &str) {
+    STANDBY_LAG_SECONDS.remove_target(target);
+    for series in [
+        &STANDBY_LAG_BYTES,
+        &REPLICATION_SLOT_RETAINED_BYTES,
+        &REPLICATION_SLOT_ACTIVE,
+        &REPLICA_THREAD_RUNNING,
+        &REPLICA_LAST_ERRNO,
+        &REPLICA_RELAY_LOG_SPACE_BYTES,
+        &REPLICA_GTID_GAP,
+        &GALERA_CLUSTER_SIZE,
+        &GALERA_STATE_INFO,
+        &GROUP_REPLICATION_MEMBER,
+    ] {
+        series.remove_target(target);
+    }
+    GALERA_FLOW_CONTROL_PAUSED.remove_target(target);
+}
+
+/// Drop the custom check gauges of a target that is stopped or reconfigured
+pub fn remove_custom_check_gauges<'a>(
+    target: &str,
+    database: &str,
+    checks: impl IntoIterator<Item = &'a str>,
+) {
+    for check in checks {
+        let _ = CUSTOM_CHECK_VALUE.remove_label_values(&[target, database, check]);
+        let _ = CUSTOM_CHECK_OK.remove_label_values(&[target, database, check]);
+    }
+}
+
+/// Custom labels of every target, one entry per target
+type TargetLabels = Vec<(String, BTreeMap<String, String>)>;
+
+/// Collector for `dbpulse_target_info`
+///
+/// Its label names come from the configuration and may change on reload, so
+/// the series are rebuilt on every scrape instead of using a fixed vector.
+#[derive(Clone)]
+pub struct TargetInfo {
+    desc: Desc,
+    targets: Arc<Mutex<TargetLabels>>,
+}
+
+impl TargetInfo {
+    const NAME: &str = "dbpulse_target_info";
+    const HELP: &str = "Target metadata, custom labels from the configuration";
+}
+
+impl Collector for TargetInfo {
+    fn desc(&self) -> Vec<&Desc> {
+        vec![&self.desc]
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let targets = self.targets.lock().unwrap_or_else(PoisonError::into_inner);
+        let names: BTreeSet<&str> = targets
+            .iter()
+            .flat_map(|(_, labels)| labels.keys().map(String::as_str))
+            .collect();
+        let label_names: Vec<&str> = std::iter::once("target").chain(names).collect();
+
+        let Ok(gauge) = IntGaugeVec::new(opts!(Self::NAME, Self::HELP), &label_names) else {
+            return Vec::new();
+        };
+        for (target, labels) in targets.iter() {
+            let values: Vec<&str> = label_names
+                .iter()
+                .map(|name| match *name {
+                    "target" => target.as_str(),
+                    name => labels.get(name).map_or("", String::as_str),
+                })
+                .collect();
+            gauge.with_label_values(&values).set(1);
+        }
+        gauge.collect()
+    }
+}
+
+pub static TARGET_INFO: LazyLock<TargetInfo> = LazyLock::new(|| {
+    let info = TargetInfo {
+        desc: Desc::new(
+            TargetInfo::NAME.to_string(),
+            TargetInfo::HELP.to_string(),
+            Vec::new(),
+            HashMap::new(),
+        )
+        .or_exit("metric can be created"),
+        targets: Arc::default(),
+    };
+    REGISTRY
+        .register(Box::new(info.clone()))
+        .or_exit("metric can be created");
+    info
+});
+
+/// Export `dbpulse_target_info{target, <labels>} 1` for every target
+///
+/// The label names are the union of all target labels, targets without a
+/// given label export it as an empty string. Calling it again replaces the
+/// previous series.
+pub fn set_target_info<'a>(
+    targets: impl IntoIterator<Item = (&'a str, &'a BTreeMap<String, String>)>,
+) {
+    let targets = targets
+        .into_iter()
+        .map(|(name, labels)| (name.to_string(), labels.clone()))
+        .collect();
+    *TARGET_INFO
+        .targets
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = targets;
+}
+
 /// Encode and return metrics for HTTP export
 ///
 /// # Errors
@@ -320,89 +1510,229 @@ mod tests {
     #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 
     use super::*;
+    use crate::queries::ErrorKind;
 
     #[test]
     fn test_metrics_initialization() {
         // Test that all metrics can be accessed without panicking
-        PULSE.set(1);
-        assert_eq!(PULSE.get(), 1);
+        PULSE.with_label_values(&["db-1"]).set(1);
+        assert_eq!(PULSE.with_label_values(&["db-1"]).get(), 1);
+    }
+
+    #[test]
+    fn test_server_series_replace() {
+        fn labels(slot: &str) -> [&str; 4] {
+            ["slots-db", "postgres", slot, "physical"]
+        }
+        let series = |slot| labels(slot).map(str::to_string).to_vec();
+        for slot in ["standby_a", "standby_b"] {
+            REPLICATION_SLOT_ACTIVE
+                .vec
+                .with_label_values(&labels(slot))
+                .set(1);
+        }
+        REPLICATION_SLOT_ACTIVE.replace(
+            "slots-db",
+            [series("standby_a"), series("standby_b")].into(),
+        );
+
+        // standby_b was dropped
+        REPLICATION_SLOT_ACTIVE.replace("slots-db", [series("standby_a")].into());
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(r#"slot="standby_a""#));
+        assert!(!output.contains(r#"slot="standby_b""#));
+
+        remove_replication_series("slots-db");
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"slot="standby_a""#));
     }
 
     #[test]
     fn test_metrics_labels() {
         // Test metrics with labels
         DB_ERRORS
-            .with_label_values(&["postgres", "connection"])
+            .with_label_values(&["db-1", "postgres", "connection"])
             .inc();
         OPERATION_DURATION
-            .with_label_values(&["postgres", "connect"])
+            .with_label_values(&["db-1", "postgres", "connect"])
             .observe(0.123);
         ROWS_AFFECTED
-            .with_label_values(&["mysql", "insert"])
+            .with_label_values(&["db-1", "mysql", "insert"])
             .inc_by(5);
         ITERATIONS_TOTAL
-            .with_label_values(&["postgres", "success"])
+            .with_label_values(&["db-1", "postgres", "success"])
             .inc();
         LAST_SUCCESS
-            .with_label_values(&["postgres"])
+            .with_label_values(&["db-1", "postgres"])
             .set(1_234_567_890);
         TABLE_SIZE_BYTES
-            .with_label_values(&["postgres", "dbpulse_rw"])
+            .vec
+            .with_label_values(&["db-1", "postgres", "dbpulse_rw"])
             .set(1024);
         TABLE_ROWS
-            .with_label_values(&["mysql", "dbpulse_rw"])
+            .vec
+            .with_label_values(&["db-1", "mysql", "dbpulse_rw"])
             .set(100);
-        DB_READONLY.with_label_values(&["postgres"]).set(0);
+        DB_READONLY.with_label_values(&["db-1", "postgres"]).set(0);
         TLS_HANDSHAKE_DURATION
-            .with_label_values(&["postgres"])
+            .with_label_values(&["db-1", "postgres"])
             .observe(0.05);
         TLS_CONNECTION_ERRORS
-            .with_label_values(&["mysql", "handshake"])
+            .with_label_values(&["db-1", "mysql", "handshake"])
             .inc();
         TLS_INFO
-            .with_label_values(&["postgres", "TLSv1.3", "AES256-GCM-SHA384"])
+            .vec
+            .with_label_values(&["db-1", "postgres", "TLSv1.3", "AES256-GCM-SHA384"])
             .set(1);
         TLS_CERT_EXPIRY_DAYS
-            .with_label_values(&["postgres"])
+            .with_label_values(&["db-1", "postgres"])
             .set(90);
         TLS_CERT_PROBE_ERRORS
-            .with_label_values(&["postgres", "handshake"])
+            .with_label_values(&["db-1", "postgres", "handshake"])
             .inc();
         DATABASE_HOST_INFO
-            .with_label_values(&["mysql", "db-node-a"])
+            .vec
+            .with_label_values(&["db-1", "mysql", "db-node-a"])
             .set(1);
     }
 
     #[test]
     fn test_histogram_metrics() {
         // Test histogram observations
-        RUNTIME.start_timer().observe_duration();
-        CONNECTION_DURATION.observe(1.5);
+        RUNTIME
+            .with_label_values(&["db-1"])
+            .start_timer()
+            .observe_duration();
+        CONNECTION_DURATION
+            .with_label_values(&["db-1", "postgres"])
+            .observe(1.5);
         OPERATION_DURATION
-            .with_label_values(&["postgres", "insert"])
+            .with_label_values(&["db-1", "postgres", "insert"])
             .observe(0.001);
         TLS_HANDSHAKE_DURATION
-            .with_label_values(&["mysql"])
+            .with_label_values(&["db-1", "mysql"])
             .observe(0.1);
     }
 
     #[test]
     fn test_counter_metrics() {
         // Test counters
-        PANICS_RECOVERED.inc();
-        DB_ERRORS.with_label_values(&["postgres", "timeout"]).inc();
+        PANICS_RECOVERED.with_label_values(&["db-1"]).inc();
+        DB_ERRORS
+            .with_label_values(&["db-1", "postgres", "timeout"])
+            .inc();
         ROWS_AFFECTED
-            .with_label_values(&["postgres", "delete"])
+            .with_label_values(&["db-1", "postgres", "delete"])
             .inc_by(10);
         TLS_CONNECTION_ERRORS
-            .with_label_values(&["postgres", "certificate"])
+            .with_label_values(&["db-1", "postgres", "certificate"])
             .inc();
     }
 
+    #[test]
+    fn test_remove_target_gauges() {
+        PULSE.with_label_values(&["removed-db"]).set(1);
+        LAST_SUCCESS
+            .with_label_values(&["removed-db", "postgres"])
+            .set(1);
+        ITERATIONS_TOTAL
+            .with_label_values(&["removed-db", "postgres", "success"])
+            .inc();
+        DATABASE_VERSION_INFO.set_single(
+            "removed-db",
+            &["removed-db", "postgres", "16.4", "postgres"],
+            1,
+        );
+        DATABASE_HOST_INFO.set_single("removed-db", &["removed-db", "postgres", "pg-1"], 1);
+        TLS_INFO.set_single(
+            "removed-db",
+            &[
+                "removed-db",
+                "postgres",
+                "TLSv1.3",
+                "TLS_AES_256_GCM_SHA384",
+            ],
+            1,
+        );
+        TABLE_ROWS.set_single("removed-db", &["removed-db", "postgres", "dbpulse_rw"], 7);
+        TABLE_SIZE_BYTES.set_single(
+            "removed-db",
+            &["removed-db", "postgres", "dbpulse_rw"],
+            8192,
+        );
+
+        remove_target_gauges("removed-db", "postgres");
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"dbpulse_pulse{target="removed-db"}"#));
+        assert!(!output.contains(
+            r#"dbpulse_last_success_timestamp_seconds{database="postgres",target="removed-db"}"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_iterations_total{database="postgres",status="success",target="removed-db"}"#
+        ));
+        for line in output
+            .lines()
+            .filter(|line| line.contains(r#"target="removed-db""#))
+        {
+            assert!(line.starts_with("dbpulse_iterations_total"), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_remove_custom_check_gauges() {
+        let labels = ["custom-db", "postgres", "pending_jobs"];
+        CUSTOM_CHECK_VALUE.with_label_values(&labels).set(42.0);
+        CUSTOM_CHECK_OK.with_label_values(&labels).set(1);
+        CUSTOM_CHECK_ERRORS.with_label_values(&labels).inc();
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_custom_check_value{check="pending_jobs",database="postgres",target="custom-db"} 42"#
+        ));
+
+        remove_custom_check_gauges("custom-db", "postgres", ["pending_jobs"]);
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"dbpulse_custom_check_ok{check="pending_jobs""#));
+        assert!(!output.contains(r#"dbpulse_custom_check_value{check="pending_jobs""#));
+        assert!(output.contains(r#"dbpulse_custom_check_errors_total{check="pending_jobs""#));
+    }
+
+    #[test]
+    fn test_config_reload_metrics() {
+        CONFIG_RELOADS.with_label_values(&["failure"]).inc();
+        CONFIG_LAST_RELOAD_SUCCESSFUL.get();
+        CONFIG_LAST_RELOAD_SUCCESS.get();
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(r#"dbpulse_config_reloads_total{result="failure"}"#));
+        assert!(output.contains("dbpulse_config_last_reload_successful "));
+        assert!(output.contains("dbpulse_config_last_reload_success_timestamp_seconds "));
+    }
+
+    #[test]
+    fn test_set_target_info() {
+        let orders = BTreeMap::from([("env".to_string(), "prod".to_string())]);
+        let cache = BTreeMap::from([("team".to_string(), "core".to_string())]);
+        set_target_info([("orders", &orders), ("cache", &cache)]);
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(r#"dbpulse_target_info{env="prod",target="orders",team=""} 1"#));
+        assert!(output.contains(r#"dbpulse_target_info{env="",target="cache",team="core"} 1"#));
+
+        // replacing the targets drops the old label set
+        let empty = BTreeMap::new();
+        set_target_info([("orders", &empty)]);
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(r#"dbpulse_target_info{target="orders"} 1"#));
+        assert!(!output.contains("team="));
+    }
+
     #[test]
     fn test_encode_metrics() {
         // Initialize at least one metric to ensure non-empty output
-        PANICS_RECOVERED.inc();
+        PANICS_RECOVERED.with_label_values(&["db-1"]).inc();
 
         // Ensure metrics can be encoded without error
         let result = encode_metrics();
@@ -418,16 +1748,17 @@ mod tests {
 
     #[test]
     fn test_registry() {
-        // Force initialization of metrics by accessing them
-        let _ = &*PULSE;
-        let _ = &*RUNTIME;
+        // Force initialization of metrics by using them
+        PULSE.with_label_values(&["db-1"]).set(1);
+        RUNTIME.with_label_values(&["db-1"]).observe(0.1);
         // Use metrics with labels to ensure they're registered
-        DB_ERRORS.with_label_values(&["test", "test"]).inc();
+        DB_ERRORS.with_label_values(&["test", "test", "test"]).inc();
         OPERATION_DURATION
-            .with_label_values(&["test", "test"])
+            .with_label_values(&["db-1", "test", "test"])
             .observe(0.1);
         DATABASE_HOST_INFO
-            .with_label_values(&["test", "db-1"])
+            .vec
+            .with_label_values(&["db-1", "test", "db-1"])
             .set(1);
 
         // Test that registry can gather metrics
@@ -448,26 +1779,23 @@ mod tests {
     #[test]
     fn test_gauge_operations() {
         // Test gauge set/get operations
-        PULSE.set(0);
-        assert_eq!(PULSE.get(), 0);
-        PULSE.set(1);
-        assert_eq!(PULSE.get(), 1);
+        let pulse = PULSE.with_label_values(&["db-1"]);
+        pulse.set(0);
+        assert_eq!(pulse.get(), 0);
+        pulse.set(1);
+        assert_eq!(pulse.get(), 1);
     }
 
     #[test]
     fn test_all_error_types() {
         // Test all error classification types
-        let error_types = [
-            "authentication",
-            "timeout",
-            "connection",
-            "transaction",
-            "query",
-        ];
-
-        for error_type in &error_types {
-            DB_ERRORS.with_label_values(&["postgres", error_type]).inc();
-            DB_ERRORS.with_label_values(&["mysql", error_type]).inc();
+        for kind in ErrorKind::ALL {
+            DB_ERRORS
+                .with_label_values(&["db-1", "postgres", kind.as_str()])
+                .inc();
+            DB_ERRORS
+                .with_label_values(&["db-1", "mysql", kind.as_str()])
+                .inc();
         }
     }
 
@@ -485,10 +1813,10 @@ mod tests {
 
         for op in &operations {
             OPERATION_DURATION
-                .with_label_values(&["postgres", op])
+                .with_label_values(&["db-1", "postgres", op])
                 .observe(0.01);
             OPERATION_DURATION
-                .with_label_values(&["mysql", op])
+                .with_label_values(&["db-1", "mysql", op])
                 .observe(0.01);
         }
     }
@@ -0,0 +1,175 @@
+//! Health of every address the hosts of a target resolve to
+//!
+//! Behind round-robin DNS, a virtual IP or a Kubernetes headless service,
+//! each connection lands on one backend and a broken one hides behind the
+//! healthy ones. With `probe_addresses`, every address of every listed host
+//! is connected to after the check, outside its iteration deadline, and the
+//! table of the read/write test is read from it; writes only go through the
+//! selected host. Results are exported per address and never fail the check.
+
+use super::{ErrorKind, resolve_host};
+use crate::{
+    metrics::{ADDRESS_CHECK_SECONDS, ADDRESS_ERRORS, ADDRESS_UP},
+    target::Target,
+    tls::{TlsConfig, TlsMode},
+};
+use anyhow::Result;
+use futures::future::join_all;
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use tokio::time;
+
+/// Check every address of the target, concurrently
+///
+/// `check` connects to an address and reads from it; each probe is bounded
+/// by the connect and statement timeouts of the target. Series of addresses that
+/// no longer resolve are removed, as are all of them when probing is off.
+pub(crate) async fn probe<F, Fut>(target: &Target, database: &str, default_port: u16, check: F)
+where
+    F: Fn(SocketAddr) -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    if !target.probe_addresses {
+        ADDRESS_UP.remove_target(&target.name);
+        ADDRESS_CHECK_SECONDS.remove_target(&target.name);
+        return;
+    }
+
+    let mut addresses = Vec::new();
+    for dsn in target.hosts() {
+        match resolve_host(&target.name, database, dsn, default_port).await {
+            Ok(resolved) => addresses.extend(resolved),
+            Err(error) => eprintln!("{}: {error:#}", target.name),
+        }
+    }
+    let mut seen = HashSet::new();
+    addresses.retain(|address| seen.insert(*address));
+
+    let limit = target.timeouts.connect + target.timeouts.statement;
+    let check = &check;
+    let probes = addresses.iter().map(|address| async move {
+        let timer = Instant::now();
+        let result = time::timeout(limit, check(*address)).await;
+        (address, timer.elapsed(), result)
+    });
+    let mut series = HashSet::new();
+    for (address, elapsed, result) in join_all(probes).await {
+        let address = address.to_string();
+        let labels = [target.name.as_str(), database, address.as_str()];
+        record(target, &labels, elapsed, result, limit);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    ADDRESS_UP.replace(&target.name, series.clone());
+    ADDRESS_CHECK_SECONDS.replace(&target.name, series);
+}
+
+fn record(
+    target: &Target,
+    labels: &[&str; 3],
+    elapsed: Duration,
+    result: Result<Result<()>, time::error::Elapsed>,
+    limit: Duration,
+) {
+    ADDRESS_CHECK_SECONDS
+        .vec
+        .with_label_values(labels)
+        .set(elapsed.as_secs_f64());
+    let kind = match result {
+        Ok(Ok(())) => {
+            ADDRESS_UP.vec.with_label_values(labels).set(1);
+            return;
+        }
+        Ok(Err(error)) => {
+            eprintln!("{}: address {} failed: {error:#}", target.name, labels[2]);
+            ErrorKind::of(&error)
+        }
+        Err(_) => {
+            eprintln!(
+                "{}: address {} timed out after {}ms",
+                target.name,
+                labels[2],
+                limit.as_millis()
+            );
+            ErrorKind::Timeout
+        }
+    };
+    ADDRESS_UP.vec.with_label_values(labels).set(0);
+    let [name, database, address] = *labels;
+    ADDRESS_ERRORS
+        .with_label_values(&[name, database, address, kind.as_str()])
+        .inc();
+}
+
+/// TLS settings for a connection to an address instead of the host name
+///
+/// The certificate cannot match an IP address, so `verify-full` only
+/// verifies the chain; the host name is still verified by the check itself.
+pub(crate) fn address_tls(tls: &TlsConfig) -> TlsConfig {
+    let mut tls = tls.clone();
+    if tls.mode == TlsMode::VerifyFull {
+        tls.mode = TlsMode::VerifyCA;
+    }
+    tls
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::{metrics::encode_metrics, queries::CheckError, target::Target};
+    use anyhow::anyhow;
+    use std::future::ready;
+
+    fn target(name: &str, probe_addresses: bool) -> Target {
+        Target {
+            probe_addresses,
+            ..Target::for_test(
+                name,
+                "mysql://u@tcp(10.0.0.1:3306,10.0.0.2,10.0.0.1:3306)/db",
+            )
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_every_address() {
+        let enabled = target("addresses-db", true);
+        probe(&enabled, "mysql", 3306, |address| {
+            ready(if address.ip().to_string() == "10.0.0.2" {
+                Err(CheckError::new(ErrorKind::Authentication, anyhow!("denied")).into())
+            } else {
+                Ok(())
+            })
+        })
+        .await;
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_address_up{address="10.0.0.1:3306",database="mysql",target="addresses-db"} 1"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_address_up{address="10.0.0.2:3306",database="mysql",target="addresses-db"} 0"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_address_errors_total{address="10.0.0.2:3306",database="mysql",error_type="authentication",target="addresses-db"} 1"#
+        ));
+
+        let disabled = target("addresses-db", false);
+        probe(&disabled, "mysql", 3306, |_| ready(Ok(()))).await;
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"dbpulse_address_up{address="10.0.0.1:3306""#));
+    }
+
+    #[test]
+    fn test_address_tls() {
+        let tls = TlsConfig {
+            mode: TlsMode::VerifyFull,
+            ..TlsConfig::default()
+        };
+        assert_eq!(address_tls(&tls).mode, TlsMode::VerifyCA);
+        assert_eq!(address_tls(&TlsConfig::default()).mode, TlsMode::Disable);
+    }
+}
@@ -0,0 +1,96 @@
+//! Pacing of collectors that run less often than the pulse
+//!
+//! Some statistics are expensive to gather or change slowly (vacuum and
+//! wraparound state, engine internals); their collectors run at most once per
+//! [`SLOW_COLLECTOR_INTERVAL`] for each target, on the check that falls due.
+//! A removed or restarted target loses its series, so its pace is forgotten
+//! and the next check collects again.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// Minimum time between two runs of a slow collector for the same target
+pub(crate) const SLOW_COLLECTOR_INTERVAL: Duration = Duration::from_mins(5);
+
+/// Pace of the vacuum and wraparound collector, per target
+pub(crate) static VACUUM_CADENCE: LazyLock<Cadence> = LazyLock::new(Cadence::default);
+
+/// Pace of the `InnoDB` collector, per target
+pub(crate) static INNODB_CADENCE: LazyLock<Cadence> = LazyLock::new(Cadence::default);
+
+/// Pace of `PRAGMA quick_check`, per target
+pub(crate) static QUICK_CHECK_CADENCE: LazyLock<Cadence> = LazyLock::new(Cadence::default);
+
+/// Pace of `PRAGMA integrity_check`, per target
+pub(crate) static INTEGRITY_CHECK_CADENCE: LazyLock<Cadence> = LazyLock::new(Cadence::default);
+
+/// Forget the last runs of every collector for a target that is no longer monitored
+pub(crate) fn forget_target(target: &str) {
+    for cadence in [
+        &VACUUM_CADENCE,
+        &INNODB_CADENCE,
+        &QUICK_CHECK_CADENCE,
+        &INTEGRITY_CHECK_CADENCE,
+    ] {
+        cadence.forget(target);
+    }
+}
+
+/// Last run of a collector, by target
+#[derive(Debug, Default)]
+pub(crate) struct Cadence {
+    last_run: Mutex<HashMap<String, Instant>>,
+}
+
+impl Cadence {
+    /// Whether the collector is due for a target, marking it as run if so
+    pub(crate) fn due(&self, target: &str, every: Duration) -> bool {
+        self.due_at(target, every, Instant::now())
+    }
+
+    fn due_at(&self, target: &str, every: Duration, now: Instant) -> bool {
+        let mut last_run = self.last_run.lock().unwrap_or_else(PoisonError::into_inner);
+        match last_run.get(target) {
+            Some(last) if now.saturating_duration_since(*last) < every => false,
+            _ => {
+                last_run.insert(target.to_string(), now);
+                true
+            }
+        }
+    }
+
+    /// Forget a target, its collector is due on the next check
+    pub(crate) fn forget(&self, target: &str) {
+        self.last_run
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+
+    #[test]
+    fn test_due_once_per_interval() {
+        let cadence = Cadence::default();
+        let start = Instant::now();
+        let every = Duration::from_mins(5);
+
+        assert!(cadence.due_at("db", every, start));
+        assert!(!cadence.due_at("db", every, start + Duration::from_secs(30)));
+        // other targets keep their own pace
+        assert!(cadence.due_at("other", every, start + Duration::from_secs(30)));
+        assert!(cadence.due_at("db", every, start + every));
+        assert!(!cadence.due_at("db", every, start + every + Duration::from_secs(1)));
+
+        cadence.forget("db");
+        assert!(cadence.due_at("db", every, start + every + Duration::from_secs(2)));
+    }
+}
@@ -0,0 +1,215 @@
+//! Galera and Group Replication state of `MySQL` and `MariaDB` targets
+//!
+//! A Galera node outside the primary component still answers queries
+//! (`wsrep_dirty_reads`) and may even report itself `Synced`, yet every
+//! write to it is lost or rejected; the check fails in that case.
+
+use super::{CheckError, ErrorKind};
+use crate::metrics::{
+    GALERA_CLUSTER_SIZE, GALERA_FLOW_CONTROL_PAUSED, GALERA_STATE_INFO, GROUP_REPLICATION_MEMBER,
+};
+use anyhow::{Result, anyhow};
+use std::collections::{HashMap, HashSet};
+
+/// Status of a Galera node, from its `wsrep_%` status variables
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GaleraStatus {
+    pub(crate) cluster_size: i64,
+    /// `wsrep_local_state_comment`, e.g. `Synced`, `Donor/Desynced`
+    pub(crate) local_state: String,
+    /// `wsrep_cluster_status`: `Primary`, `non-Primary` or `Disconnected`
+    pub(crate) cluster_status: String,
+    pub(crate) flow_control_paused: f64,
+}
+
+impl GaleraStatus {
+    #[must_use]
+    pub(crate) fn parse(status: &HashMap<String, String>) -> Self {
+        let value = |name: &str| status.get(name).map(|value| value.trim());
+        Self {
+            cluster_size: value("wsrep_cluster_size")
+                .and_then(|size| size.parse().ok())
+                .unwrap_or_default(),
+            local_state: value("wsrep_local_state_comment")
+                .unwrap_or_default()
+                .to_string(),
+            cluster_status: value("wsrep_cluster_status")
+                .unwrap_or_default()
+                .to_string(),
+            flow_control_paused: value("wsrep_flow_control_paused")
+                .and_then(|paused| paused.parse().ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Fail unless the node belongs to the primary component
+    ///
+    /// # Errors
+    ///
+    /// Returns a `cluster` error for a `non-Primary` or `Disconnected` node
+    pub(crate) fn ensure_primary(&self) -> Result<()> {
+        if self.cluster_status == "Primary" {
+            return Ok(());
+        }
+        Err(CheckError::new(
+            ErrorKind::Cluster,
+            anyhow!(
+                "Galera node is {} in a {} component of {} node(s)",
+                self.local_state,
+                self.cluster_status,
+                self.cluster_size
+            ),
+        )
+        .into())
+    }
+}
+
+/// A row of `performance_schema.replication_group_members`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GroupMember {
+    /// `host:port`, the member id while the host is not known yet
+    pub(crate) member: String,
+    /// `ONLINE`, `RECOVERING`, `OFFLINE`, `ERROR` or `UNREACHABLE`
+    pub(crate) state: String,
+    /// `PRIMARY` or `SECONDARY`, empty while the member is not online
+    pub(crate) role: String,
+}
+
+/// Record the Galera gauges of a target, `None` removes them
+pub(crate) fn record_galera(target: &str, database: &str, status: Option<&GaleraStatus>) {
+    let Some(status) = status else {
+        GALERA_CLUSTER_SIZE.remove_target(target);
+        GALERA_STATE_INFO.remove_target(target);
+        GALERA_FLOW_CONTROL_PAUSED.remove_target(target);
+        return;
+    };
+
+    let labels = [target, database];
+    GALERA_CLUSTER_SIZE
+        .vec
+        .with_label_values(&labels)
+        .set(status.cluster_size);
+    GALERA_FLOW_CONTROL_PAUSED
+        .vec
+        .with_label_values(&labels)
+        .set(status.flow_control_paused);
+    let series = HashSet::from([labels.map(str::to_string).to_vec()]);
+    GALERA_CLUSTER_SIZE.replace(target, series.clone());
+    GALERA_FLOW_CONTROL_PAUSED.replace(target, series);
+
+    let labels = [
+        target,
+        database,
+        status.local_state.as_str(),
+        status.cluster_status.as_str(),
+    ];
+    GALERA_STATE_INFO.vec.with_label_values(&labels).set(1);
+    GALERA_STATE_INFO.replace(target, HashSet::from([labels.map(str::to_string).to_vec()]));
+}
+
+/// Record the Group Replication members seen by a target, empty removes them
+pub(crate) fn record_group_members(target: &str, database: &str, members: &[GroupMember]) {
+    let mut series = HashSet::new();
+    for member in members {
+        let labels = [
+            target,
+            database,
+            member.member.as_str(),
+            member.state.as_str(),
+            member.role.as_str(),
+        ];
+        GROUP_REPLICATION_MEMBER
+            .vec
+            .with_label_values(&labels)
+            .set(1);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    GROUP_REPLICATION_MEMBER.replace(target, series);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+
+    fn status(pairs: &[(&str, &str)]) -> GaleraStatus {
+        GaleraStatus::parse(
+            &pairs
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_galera_primary() {
+        let synced = status(&[
+            ("wsrep_cluster_size", "3"),
+            ("wsrep_local_state_comment", "Synced"),
+            ("wsrep_cluster_status", "Primary"),
+            ("wsrep_flow_control_paused", "0.125"),
+        ]);
+        assert_eq!(synced.cluster_size, 3);
+        assert!((synced.flow_control_paused - 0.125).abs() < f64::EPSILON);
+        assert!(synced.ensure_primary().is_ok());
+    }
+
+    #[test]
+    fn test_galera_non_primary_fails() {
+        let partitioned = status(&[
+            ("wsrep_cluster_size", "1"),
+            ("wsrep_local_state_comment", "Synced"),
+            ("wsrep_cluster_status", "non-Primary"),
+        ]);
+        let error = partitioned.ensure_primary().unwrap_err();
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Cluster);
+        assert_eq!(
+            error.to_string(),
+            "Galera node is Synced in a non-Primary component of 1 node(s)"
+        );
+    }
+
+    #[test]
+    fn test_record_galera_and_members() {
+        let galera = status(&[
+            ("wsrep_cluster_size", "3"),
+            ("wsrep_local_state_comment", "Donor/Desynced"),
+            ("wsrep_cluster_status", "Primary"),
+        ]);
+        record_galera("galera-db", "mysql", Some(&galera));
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(
+            output
+                .contains(r#"dbpulse_galera_cluster_size{database="mysql",target="galera-db"} 3"#)
+        );
+        assert!(output.contains(r#"local_state="Donor/Desynced""#));
+
+        record_galera("galera-db", "mysql", None);
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"target="galera-db""#));
+
+        let member = |host: &str, state: &str, role: &str| GroupMember {
+            member: host.to_string(),
+            state: state.to_string(),
+            role: role.to_string(),
+        };
+        record_group_members(
+            "group-db",
+            "mysql",
+            &[
+                member("db-1:3306", "ONLINE", "PRIMARY"),
+                member("db-2:3306", "UNREACHABLE", "SECONDARY"),
+            ],
+        );
+        record_group_members(
+            "group-db",
+            "mysql",
+            &[member("db-1:3306", "ONLINE", "PRIMARY")],
+        );
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(r#"member="db-1:3306""#));
+        assert!(!output.contains(r#"member="db-2:3306""#));
+    }
+}
@@ -0,0 +1,242 @@
+//! `PostgreSQL`-compatible engines reached through the postgres driver
+//!
+//! `CockroachDB` and `YugabyteDB` speak the `PostgreSQL` wire protocol, but
+//! lack several of its functions and catalogs (`pg_is_in_recovery()`,
+//! `pg_stat_ssl`, `pg_blocking_pids()`, ...) or give them another meaning.
+//! The engine is detected from `version()` on every check, so a `postgres://`
+//! DSN works too; the `cockroach://` and `yugabyte://` drivers only change the
+//! default port. Probes the engine does not support are skipped.
+
+use crate::metrics::{CLUSTER_NODE_STATES, CLUSTER_NODES};
+use anyhow::Error;
+use sqlx::error::DatabaseError;
+
+/// Attempts of the rollback test on engines that ask clients to retry
+pub(crate) const MAX_TRANSACTION_ATTEMPTS: u32 = 3;
+
+/// Engine answering a postgres driver connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Engine {
+    #[default]
+    Postgres,
+    Cockroach,
+    Yugabyte,
+}
+
+/// Best-effort probes that only some engines support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Probe {
+    /// `pg_is_in_recovery()`, replay lag, `pg_stat_replication` and replication slots
+    Standbys,
+    /// `pg_blocking_pids()` and `pg_locks`
+    LockWaits,
+    /// Connection states of `pg_stat_activity` and `max_connections`
+    Sessions,
+    /// XID ages, autovacuum workers and dead tuples
+    Vacuum,
+    /// TLS version and cipher from `pg_stat_ssl`
+    SslStats,
+    /// `pg_total_relation_size()`, `pg_database_size()` and `pg_class.reltuples`
+    Sizes,
+}
+
+impl Engine {
+    /// Engine expected by the driver of a DSN, the default for `postgres`
+    #[must_use]
+    pub(crate) fn from_driver(driver: &str) -> Self {
+        match driver {
+            "cockroach" | "cockroachdb" => Self::Cockroach,
+            "yugabyte" | "yugabytedb" => Self::Yugabyte,
+            _ => Self::Postgres,
+        }
+    }
+
+    /// Engine answering a connection, from the text of `version()`
+    #[must_use]
+    pub(crate) fn detect(version: &str) -> Self {
+        if version.starts_with("CockroachDB") {
+            Self::Cockroach
+        } else if version.contains("-YB-") {
+            Self::Yugabyte
+        } else {
+            Self::Postgres
+        }
+    }
+
+    /// Value of the `flavor` label of `dbpulse_database_version_info`
+    #[must_use]
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Postgres => "postgres",
+            Self::Cockroach => "cockroachdb",
+            Self::Yugabyte => "yugabytedb",
+        }
+    }
+
+    /// SQL port of the engine, used when the DSN does not give one
+    #[must_use]
+    pub(crate) const fn default_port(self) -> u16 {
+        match self {
+            Self::Postgres => 5432,
+            Self::Cockroach => 26257,
+            // YSQL, the PostgreSQL-compatible API
+            Self::Yugabyte => 5433,
+        }
+    }
+
+    #[must_use]
+    pub(crate) const fn supports(self, probe: Probe) -> bool {
+        match self {
+            Self::Postgres => true,
+            Self::Cockroach => false,
+            Self::Yugabyte => matches!(probe, Probe::Sessions | Probe::SslStats),
+        }
+    }
+
+    /// Whether serialization failures (`40001`) are expected and retried,
+    /// transactions are serializable and optimistic by default
+    #[must_use]
+    pub(crate) const fn retries_transactions(self) -> bool {
+        matches!(self, Self::Cockroach | Self::Yugabyte)
+    }
+
+    /// Version exported in `dbpulse_database_version_info`, `None` to use `server_version`
+    ///
+    /// `server_version` is the `PostgreSQL` version an engine emulates,
+    /// `version()` names the engine and its own release.
+    #[must_use]
+    pub(crate) fn version(self, version: &str) -> Option<String> {
+        match self {
+            Self::Postgres => None,
+            // CockroachDB CCL v23.1.11 (x86_64-pc-linux-gnu, built 2023/09/27 ...)
+            Self::Cockroach => Some(
+                version
+                    .split_once(" (")
+                    .map_or(version, |(release, _)| release)
+                    .trim()
+                    .to_string(),
+            ),
+            // PostgreSQL 11.2-YB-2.18.0.0-b0 on x86_64-pc-linux-gnu, ...
+            Self::Yugabyte => version
+                .split_once("-YB-")
+                .and_then(|(_, rest)| rest.split_whitespace().next())
+                .map(|release| format!("YugabyteDB {release}")),
+        }
+    }
+}
+
+/// Whether `error` is a serialization failure the client should retry
+pub(crate) fn is_retryable(error: &Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<sqlx::Error>()
+            .and_then(sqlx::Error::as_database_error)
+            .and_then(DatabaseError::code)
+            .is_some_and(|code| code == "40001")
+    })
+}
+
+/// Nodes of a `CockroachDB` or `YugabyteDB` cluster by state
+///
+/// `YugabyteDB` only lists the live tablet servers (`yb_servers()`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ClusterNodes {
+    pub(crate) live: i64,
+    /// Draining or decommissioning
+    pub(crate) draining: Option<i64>,
+    pub(crate) unavailable: Option<i64>,
+}
+
+/// Export the nodes of a cluster, removing the states the engine does not report
+pub(crate) fn record(target: &str, database: &str, nodes: ClusterNodes) {
+    for (state, count) in
+        CLUSTER_NODE_STATES
+            .into_iter()
+            .zip([Some(nodes.live), nodes.draining, nodes.unavailable])
+    {
+        let labels = [target, database, state];
+        match count {
+            Some(count) => CLUSTER_NODES.with_label_values(&labels).set(count),
+            None => {
+                let _ = CLUSTER_NODES.remove_label_values(&labels);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+
+    const COCKROACH: &str =
+        "CockroachDB CCL v23.1.11 (x86_64-pc-linux-gnu, built 2023/09/27 01:53:43, go1.19.10)";
+    const YUGABYTE: &str = "PostgreSQL 11.2-YB-2.18.0.0-b0 on x86_64-pc-linux-gnu, compiled by clang version 15.0.3, 64-bit";
+    const POSTGRES: &str = "PostgreSQL 17.0 on x86_64-pc-linux-gnu, compiled by gcc 12.2.0, 64-bit";
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Engine::detect(COCKROACH), Engine::Cockroach);
+        assert_eq!(Engine::detect(YUGABYTE), Engine::Yugabyte);
+        assert_eq!(Engine::detect(POSTGRES), Engine::Postgres);
+        assert_eq!(Engine::from_driver("cockroach"), Engine::Cockroach);
+        assert_eq!(Engine::from_driver("yugabytedb"), Engine::Yugabyte);
+        assert_eq!(Engine::from_driver("postgresql").default_port(), 5432);
+        assert_eq!(Engine::Cockroach.default_port(), 26257);
+    }
+
+    #[test]
+    fn test_version() {
+        assert_eq!(
+            Engine::Cockroach.version(COCKROACH).unwrap(),
+            "CockroachDB CCL v23.1.11"
+        );
+        assert_eq!(
+            Engine::Yugabyte.version(YUGABYTE).unwrap(),
+            "YugabyteDB 2.18.0.0-b0"
+        );
+        assert_eq!(Engine::Postgres.version(POSTGRES), None);
+    }
+
+    #[test]
+    fn test_supports() {
+        assert!(Engine::Postgres.supports(Probe::Vacuum));
+        assert!(!Engine::Cockroach.supports(Probe::Standbys));
+        assert!(!Engine::Cockroach.supports(Probe::SslStats));
+        assert!(Engine::Yugabyte.supports(Probe::Sessions));
+        assert!(!Engine::Yugabyte.supports(Probe::LockWaits));
+    }
+
+    #[test]
+    fn test_record_cluster_nodes() {
+        record(
+            "crdb",
+            "postgres",
+            ClusterNodes {
+                live: 2,
+                draining: Some(1),
+                unavailable: Some(0),
+            },
+        );
+        record(
+            "yb",
+            "postgres",
+            ClusterNodes {
+                live: 3,
+                ..ClusterNodes::default()
+            },
+        );
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_cluster_nodes{database="postgres",state="draining",target="crdb"} 1"#
+        ));
+        assert!(
+            output.contains(
+                r#"dbpulse_cluster_nodes{database="postgres",state="live",target="yb"} 3"#
+            )
+        );
+        assert!(!output.contains(r#"state="unavailable",target="yb""#));
+    }
+}
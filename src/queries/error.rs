@@ -0,0 +1,334 @@
+use super::DeadlineExceeded;
+use serde::{Serialize, Serializer};
+use sqlx::error::DatabaseError;
+use std::{error::Error as StdError, fmt, io};
+
+/// Category of a failed check, exported as the `error_type` label
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    Authentication,
+    Connection,
+    Dns,
+    Timeout,
+    TooManyConnections,
+    AdminShutdown,
+    DiskFull,
+    Deadlock,
+    Tls,
+    TlsVerify,
+    ReadOnly,
+    /// A cluster node cut off from the primary component
+    Cluster,
+    Transaction,
+    Query,
+}
+
+impl ErrorKind {
+    pub const ALL: [Self; 14] = [
+        Self::Authentication,
+        Self::Connection,
+        Self::Dns,
+        Self::Timeout,
+        Self::TooManyConnections,
+        Self::AdminShutdown,
+        Self::DiskFull,
+        Self::Deadlock,
+        Self::Tls,
+        Self::TlsVerify,
+        Self::ReadOnly,
+        Self::Cluster,
+        Self::Transaction,
+        Self::Query,
+    ];
+
+    /// Value of the `error_type` label
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Authentication => "authentication",
+            Self::Connection => "connection",
+            Self::Dns => "dns",
+            Self::Timeout => "timeout",
+            Self::TooManyConnections => "too_many_connections",
+            Self::AdminShutdown => "admin_shutdown",
+            Self::DiskFull => "disk_full",
+            Self::Deadlock => "deadlock",
+            Self::Tls => "tls",
+            Self::TlsVerify => "tls_verify",
+            Self::ReadOnly => "read_only",
+            Self::Cluster => "cluster",
+            Self::Transaction => "transaction",
+            Self::Query => "query",
+        }
+    }
+
+    /// Whether the error happened while negotiating or verifying TLS
+    #[must_use]
+    pub const fn is_tls(self) -> bool {
+        matches!(self, Self::Tls | Self::TlsVerify)
+    }
+
+    /// Category of an error returned by a check, `Query` if it was not classified
+    #[must_use]
+    pub fn of(error: &anyhow::Error) -> Self {
+        error
+            .chain()
+            .find_map(|cause| {
+                if cause.is::<DeadlineExceeded>() {
+                    return Some(Self::Timeout);
+                }
+                cause.downcast_ref::<CheckError>().map(|check| check.kind)
+            })
+            .unwrap_or(Self::Query)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Maps a server error of a driver to a category, `None` if not specific
+pub(crate) type ServerErrorKind = fn(&dyn DatabaseError) -> Option<ErrorKind>;
+
+/// Error of a failed check together with its category
+///
+/// Displays and chains exactly like the wrapped error, so log lines and
+/// `/status` messages are unchanged.
+#[derive(Debug)]
+pub struct CheckError {
+    kind: ErrorKind,
+    source: anyhow::Error,
+}
+
+impl CheckError {
+    #[must_use]
+    pub fn new(kind: ErrorKind, source: anyhow::Error) -> Self {
+        Self { kind, source }
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Attach a category to an error, `fallback` if no cause is recognized
+    ///
+    /// Errors that already carry a category, including deadlines, are
+    /// returned unchanged.
+    pub(crate) fn classify(
+        error: anyhow::Error,
+        server: ServerErrorKind,
+        fallback: ErrorKind,
+    ) -> anyhow::Error {
+        if error.is::<Self>() || error.chain().any(<dyn StdError>::is::<DeadlineExceeded>) {
+            return error;
+        }
+        let kind = error
+            .chain()
+            .find_map(|cause| cause_kind(cause, server))
+            .unwrap_or(fallback);
+        Self::new(kind, error).into()
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.source, f)
+    }
+}
+
+impl StdError for CheckError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source.source()
+    }
+}
+
+fn cause_kind(cause: &(dyn StdError + 'static), server: ServerErrorKind) -> Option<ErrorKind> {
+    if let Some(check) = cause.downcast_ref::<CheckError>() {
+        return Some(check.kind);
+    }
+    if cause.is::<DeadlineExceeded>() {
+        return Some(ErrorKind::Timeout);
+    }
+    if let Some(error) = cause.downcast_ref::<sqlx::Error>() {
+        return sqlx_kind(error, server);
+    }
+    cause.downcast_ref::<io::Error>().map(io_kind)
+}
+
+fn sqlx_kind(error: &sqlx::Error, server: ServerErrorKind) -> Option<ErrorKind> {
+    match error {
+        sqlx::Error::Database(db_err) => server(db_err.as_ref()),
+        sqlx::Error::Io(io_err) => Some(io_kind(io_err)),
+        sqlx::Error::Tls(tls_err) => Some(tls_kind(tls_err.as_ref())),
+        sqlx::Error::PoolTimedOut => Some(ErrorKind::Timeout),
+        sqlx::Error::Configuration(_)
+        | sqlx::Error::Protocol(_)
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => Some(ErrorKind::Connection),
+        _ => None,
+    }
+}
+
+fn io_kind(error: &io::Error) -> ErrorKind {
+    // rustls reports handshake failures as io errors wrapping `rustls::Error`
+    if let Some(inner) = error.get_ref()
+        && inner.is::<rustls::Error>()
+    {
+        return tls_kind(inner);
+    }
+    match error.kind() {
+        io::ErrorKind::TimedOut => ErrorKind::Timeout,
+        _ => ErrorKind::Connection,
+    }
+}
+
+fn tls_kind(error: &(dyn StdError + 'static)) -> ErrorKind {
+    let mut cause = Some(error);
+    while let Some(current) = cause {
+        let rustls_error = current.downcast_ref::<rustls::Error>().or_else(|| {
+            current
+                .downcast_ref::<io::Error>()
+                .and_then(io::Error::get_ref)
+                .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+        });
+        if let Some(rustls_error) = rustls_error {
+            return match rustls_error {
+                rustls::Error::InvalidCertificate(_) | rustls::Error::NoCertificatesPresented => {
+                    ErrorKind::TlsVerify
+                }
+                _ => ErrorKind::Tls,
+            };
+        }
+        cause = current.source();
+    }
+    ErrorKind::Tls
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use anyhow::anyhow;
+    use std::time::Duration;
+
+    fn no_server_kind(_: &dyn DatabaseError) -> Option<ErrorKind> {
+        None
+    }
+
+    fn classify(error: anyhow::Error, fallback: ErrorKind) -> ErrorKind {
+        ErrorKind::of(&CheckError::classify(error, no_server_kind, fallback))
+    }
+
+    #[test]
+    fn test_labels_are_unique() {
+        let labels: std::collections::HashSet<&str> =
+            ErrorKind::ALL.iter().map(|kind| kind.as_str()).collect();
+        assert_eq!(labels.len(), ErrorKind::ALL.len());
+        assert_eq!(
+            ErrorKind::TooManyConnections.to_string(),
+            "too_many_connections"
+        );
+    }
+
+    #[test]
+    fn test_messages_do_not_decide_the_category() {
+        // words that used to be matched in the message
+        let error = anyhow!("password column is missing, ssl timeout connection");
+        assert_eq!(classify(error, ErrorKind::Query), ErrorKind::Query);
+        assert_eq!(
+            ErrorKind::of(&anyhow!("authentication failed")),
+            ErrorKind::Query
+        );
+    }
+
+    #[test]
+    fn test_fallback_and_existing_kind() {
+        let error = anyhow!("Transaction rollback failed: value is still 0");
+        assert_eq!(
+            classify(error, ErrorKind::Transaction),
+            ErrorKind::Transaction
+        );
+
+        let dns = CheckError::new(ErrorKind::Dns, anyhow!("no such host")).into();
+        let wrapped = CheckError::classify(dns, no_server_kind, ErrorKind::Query);
+        assert_eq!(ErrorKind::of(&wrapped), ErrorKind::Dns);
+    }
+
+    #[test]
+    fn test_io_errors() {
+        let refused = sqlx::Error::Io(io::Error::from(io::ErrorKind::ConnectionRefused));
+        assert_eq!(
+            classify(refused.into(), ErrorKind::Query),
+            ErrorKind::Connection
+        );
+
+        let timed_out = sqlx::Error::Io(io::Error::from(io::ErrorKind::TimedOut));
+        assert_eq!(
+            classify(timed_out.into(), ErrorKind::Query),
+            ErrorKind::Timeout
+        );
+    }
+
+    #[test]
+    fn test_tls_errors() {
+        let verify = sqlx::Error::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer),
+        ));
+        assert_eq!(
+            classify(verify.into(), ErrorKind::Query),
+            ErrorKind::TlsVerify
+        );
+
+        let handshake = sqlx::Error::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            rustls::Error::HandshakeNotComplete,
+        ));
+        assert_eq!(classify(handshake.into(), ErrorKind::Query), ErrorKind::Tls);
+
+        let config = sqlx::Error::Tls("Invalid certificate /etc/ssl/ca.crt".into());
+        assert_eq!(classify(config.into(), ErrorKind::Query), ErrorKind::Tls);
+        assert!(ErrorKind::TlsVerify.is_tls());
+        assert!(!ErrorKind::Connection.is_tls());
+    }
+
+    #[test]
+    fn test_deadline_is_timeout() {
+        let error = anyhow::Error::new(DeadlineExceeded {
+            operation: "connect",
+            limit: Duration::from_secs(1),
+        });
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Timeout);
+
+        // left unwrapped so callers can still downcast to the deadline
+        let error = CheckError::classify(error, no_server_kind, ErrorKind::Query);
+        assert_eq!(
+            error.downcast_ref::<DeadlineExceeded>().unwrap().operation,
+            "connect"
+        );
+    }
+
+    #[test]
+    fn test_check_error_keeps_message_and_chain() {
+        let error = CheckError::classify(
+            anyhow!("connection refused").context("Failed to connect"),
+            no_server_kind,
+            ErrorKind::Connection,
+        );
+        assert_eq!(error.to_string(), "Failed to connect");
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed to connect: connection refused"
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Servers reached through the `MySQL` driver
+//!
+//! `TiDB` and `Vitess` (`VTGate`) speak the `MySQL` protocol, but have no
+//! replication status, `InnoDB` internals or meaningful processlist, and
+//! estimate `table_rows`; asking them only produces errors and misleading
+//! metrics. The flavor is fingerprinted from `VERSION()` on every check and
+//! selects the probes that run. Aurora reports a plain `MySQL` version and is
+//! only recognized by its `@@aurora_version` variable; servers that look like
+//! `MySQL` are only asked once per target, host and version, since every other
+//! server rejects that variable.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, PoisonError},
+};
+
+/// Flavors detected from server variables, by target
+pub(crate) static DETECTED: LazyLock<FlavorCache> = LazyLock::new(FlavorCache::default);
+
+/// Server answering a `MySQL` driver connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Flavor {
+    #[default]
+    MySql,
+    MariaDb,
+    Percona,
+    TiDb,
+    Vitess,
+    Aurora,
+}
+
+/// Best-effort probes that only some flavors support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Probe {
+    /// `@@hostname`, the server behind a proxy is not the one answering
+    Hostname,
+    /// `SHOW REPLICA STATUS` and its predecessors
+    ReplicaStatus,
+    /// Galera and Group Replication membership
+    Cluster,
+    /// `information_schema.innodb_trx` lock waits and blockers
+    LockWaits,
+    /// Connection states from `information_schema.processlist`
+    Sessions,
+    /// `table_rows` and sizes from `information_schema.TABLES`
+    TableStats,
+    /// The `InnoDB` collector
+    Innodb,
+}
+
+impl Flavor {
+    /// Fingerprint a server from `VERSION()` and, when it is not conclusive,
+    /// `@@version_comment` and `@@aurora_version`
+    ///
+    /// `8.0.11-TiDB-v7.5.0`, `8.0.30-Vitess`, `11.4.5-MariaDB-log` and
+    /// Percona's `8.0.34-26` name the flavor. Aurora answers `8.0.28`, only
+    /// Aurora has `@@aurora_version` (`3.04.0`).
+    #[must_use]
+    pub(crate) fn detect(
+        version: &str,
+        comment: Option<&str>,
+        aurora_version: Option<&str>,
+    ) -> Self {
+        let lowercase = version.to_ascii_lowercase();
+        if lowercase.contains("tidb") {
+            Self::TiDb
+        } else if lowercase.contains("vitess") {
+            Self::Vitess
+        } else if version.contains("MariaDB") {
+            Self::MariaDb
+        } else if aurora_version.is_some() {
+            Self::Aurora
+        } else if comment.is_some_and(|comment| comment.contains("Percona"))
+            || version.split_once('-').is_some_and(|(_, release)| {
+                !release.is_empty() && release.chars().all(|c| c.is_ascii_digit() || c == '.')
+            })
+        {
+            Self::Percona
+        } else {
+            Self::MySql
+        }
+    }
+
+    /// Value of the `flavor` label of `dbpulse_database_version_info`
+    #[must_use]
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::MySql => "mysql",
+            Self::MariaDb => "mariadb",
+            Self::Percona => "percona",
+            Self::TiDb => "tidb",
+            Self::Vitess => "vitess",
+            Self::Aurora => "aurora",
+        }
+    }
+
+    #[must_use]
+    pub(crate) const fn supports(self, probe: Probe) -> bool {
+        match self {
+            Self::MySql | Self::MariaDb | Self::Percona => true,
+            // Aurora replicas share the storage, there is no Galera nor Group Replication
+            Self::Aurora => !matches!(probe, Probe::Cluster),
+            // the TiDB server answering is meaningful, the rest is not InnoDB
+            Self::TiDb => matches!(probe, Probe::Hostname),
+            Self::Vitess => false,
+        }
+    }
+}
+
+/// Flavor of each target, with the host and version it was detected on
+#[derive(Debug, Default)]
+pub(crate) struct FlavorCache {
+    flavors: Mutex<HashMap<String, (String, String, Flavor)>>,
+}
+
+impl FlavorCache {
+    /// Flavor detected for a target, unless the host or version changed since
+    pub(crate) fn get(&self, target: &str, host: &str, version: &str) -> Option<Flavor> {
+        self.flavors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(target)
+            .filter(|(cached_host, cached_version, _)| {
+                cached_host == host && cached_version == version
+            })
+            .map(|(_, _, flavor)| *flavor)
+    }
+
+    pub(crate) fn insert(&self, target: &str, host: &str, version: &str, flavor: Flavor) {
+        self.flavors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                target.to_string(),
+                (host.to_string(), version.to_string(), flavor),
+            );
+    }
+
+    /// Forget a target that is no longer monitored
+    pub(crate) fn forget(&self, target: &str) {
+        self.flavors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        for (version, comment, aurora_version, flavor) in [
+            ("8.0.11-TiDB-v7.5.0", None, None, Flavor::TiDb),
+            ("8.0.30-Vitess", None, None, Flavor::Vitess),
+            ("5.7.9-vitess-17.0.0", None, None, Flavor::Vitess),
+            ("11.4.5-MariaDB-log", None, None, Flavor::MariaDb),
+            (
+                "5.5.5-10.6.12-MariaDB-1:10.6.12+maria~ubu2004",
+                None,
+                None,
+                Flavor::MariaDb,
+            ),
+            (
+                "8.0.28",
+                Some("Source distribution"),
+                Some("3.04.0"),
+                Flavor::Aurora,
+            ),
+            (
+                "5.7.12-log",
+                Some("MySQL Community Server (GPL)"),
+                Some("2.11.2"),
+                Flavor::Aurora,
+            ),
+            ("8.0.34-26", None, None, Flavor::Percona),
+            ("8.0.33-25.1", None, None, Flavor::Percona),
+            (
+                "8.0.36",
+                Some("Percona Server (GPL), Release 28"),
+                None,
+                Flavor::Percona,
+            ),
+            (
+                "8.0.36",
+                Some("MySQL Community Server - GPL"),
+                None,
+                Flavor::MySql,
+            ),
+            ("8.0.28", Some("Source distribution"), None, Flavor::MySql),
+            ("8.0.35-0ubuntu0.22.04.1", None, None, Flavor::MySql),
+            ("5.7.44-log", None, None, Flavor::MySql),
+        ] {
+            assert_eq!(
+                Flavor::detect(version, comment, aurora_version),
+                flavor,
+                "{version}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cache_per_host_and_version() {
+        let cache = FlavorCache::default();
+        assert_eq!(cache.get("db", "aurora-1:3306", "8.0.28"), None);
+
+        cache.insert("db", "aurora-1:3306", "8.0.28", Flavor::Aurora);
+        assert_eq!(
+            cache.get("db", "aurora-1:3306", "8.0.28"),
+            Some(Flavor::Aurora)
+        );
+        // a failover or an upgrade is detected again
+        assert_eq!(cache.get("db", "aurora-2:3306", "8.0.28"), None);
+        assert_eq!(cache.get("db", "aurora-1:3306", "8.0.32"), None);
+        assert_eq!(cache.get("other", "aurora-1:3306", "8.0.28"), None);
+
+        cache.forget("db");
+        assert_eq!(cache.get("db", "aurora-1:3306", "8.0.28"), None);
+    }
+
+    #[test]
+    fn test_supports() {
+        assert!(Flavor::Percona.supports(Probe::Cluster));
+        assert!(!Flavor::Aurora.supports(Probe::Cluster));
+        assert!(Flavor::Aurora.supports(Probe::Innodb));
+        assert!(Flavor::TiDb.supports(Probe::Hostname));
+        assert!(!Flavor::TiDb.supports(Probe::TableStats));
+        assert!(!Flavor::Vitess.supports(Probe::ReplicaStatus));
+    }
+}
@@ -0,0 +1,162 @@
+//! GTID arithmetic for the replication gap of `MySQL` and `MariaDB` channels
+//!
+//! The gap is the number of transactions a replica has retrieved from its
+//! source but not yet applied.
+
+use std::collections::BTreeMap;
+
+/// Transactions in a `MySQL` GTID set, by source UUID and tag
+type GtidSet = BTreeMap<(String, String), Vec<(u64, u64)>>;
+
+/// Transactions of `retrieved` missing from `executed`, `MySQL` GTID sets
+///
+/// Sets look like `3e11fa47-...:1-5:11-18,2174b383-...:1-3`; `MySQL` 8.4
+/// tagged GTIDs (`uuid:tag:1-5`) are supported. Returns `None` if a set
+/// cannot be parsed.
+#[must_use]
+pub fn mysql_gap(retrieved: &str, executed: &str) -> Option<u64> {
+    let retrieved = parse_mysql(retrieved)?;
+    let executed = parse_mysql(executed)?;
+
+    let mut gap = 0;
+    for (source, intervals) in &retrieved {
+        let applied = executed.get(source).map_or(&[][..], Vec::as_slice);
+        for &(start, end) in intervals {
+            gap += missing(start, end, applied);
+        }
+    }
+    Some(gap)
+}
+
+/// Transactions received but not applied, `MariaDB` `Gtid_IO_Pos` and `Gtid_Slave_Pos`
+///
+/// Positions look like `0-1-100,1-2-50`: the last sequence number per
+/// replication domain. Returns `None` if a position cannot be parsed.
+#[must_use]
+pub fn mariadb_gap(io_pos: &str, slave_pos: &str) -> Option<u64> {
+    let received = parse_mariadb(io_pos)?;
+    let applied = parse_mariadb(slave_pos)?;
+    Some(
+        received
+            .iter()
+            .map(|(domain, seq)| seq.saturating_sub(applied.get(domain).copied().unwrap_or(0)))
+            .sum(),
+    )
+}
+
+fn parse_mysql(set: &str) -> Option<GtidSet> {
+    let mut parsed = GtidSet::new();
+    for source in set.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let mut parts = source.split(':');
+        let uuid = parts.next()?.to_ascii_lowercase();
+        let mut tag = String::new();
+        for part in parts {
+            if part.starts_with(|c: char| c.is_ascii_digit()) {
+                let (start, end) = part.split_once('-').unwrap_or((part, part));
+                let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+                parsed
+                    .entry((uuid.clone(), tag.clone()))
+                    .or_default()
+                    .push((start, end));
+            } else {
+                part.clone_into(&mut tag);
+            }
+        }
+    }
+    for intervals in parsed.values_mut() {
+        intervals.sort_unstable();
+    }
+    Some(parsed)
+}
+
+fn parse_mariadb(pos: &str) -> Option<BTreeMap<u32, u64>> {
+    let mut parsed = BTreeMap::new();
+    for gtid in pos.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let mut parts = gtid.split('-');
+        let domain: u32 = parts.next()?.parse().ok()?;
+        let _server: u32 = parts.next()?.parse().ok()?;
+        let seq: u64 = parts.next()?.parse().ok()?;
+        parsed.insert(domain, seq);
+    }
+    Some(parsed)
+}
+
+/// Numbers of `start..=end` not covered by the sorted `applied` intervals
+fn missing(start: u64, end: u64, applied: &[(u64, u64)]) -> u64 {
+    let mut missing = 0;
+    let mut next = start;
+    for &(from, to) in applied {
+        if to < next {
+            continue;
+        }
+        if from > end {
+            break;
+        }
+        if from > next {
+            missing += from - next;
+        }
+        next = to.saturating_add(1);
+        if next > end {
+            return missing;
+        }
+    }
+    missing + (end + 1).saturating_sub(next)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+
+    const A: &str = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+    const B: &str = "2174b383-5441-11e8-b90a-c80aa9429562";
+
+    #[test]
+    fn test_mysql_gap() {
+        assert_eq!(mysql_gap("", ""), Some(0));
+        assert_eq!(
+            mysql_gap(&format!("{A}:1-10"), &format!("{A}:1-10")),
+            Some(0)
+        );
+        assert_eq!(
+            mysql_gap(&format!("{A}:1-10"), &format!("{A}:1-7")),
+            Some(3)
+        );
+        // holes in the executed set and a source not applied at all
+        assert_eq!(
+            mysql_gap(
+                &format!("{A}:1-20,\n{B}:1-3"),
+                &format!("{}:1-5:11-18", A.to_uppercase())
+            ),
+            Some(5 + 2 + 3)
+        );
+        // transactions executed locally but never retrieved do not count
+        assert_eq!(mysql_gap(&format!("{A}:5"), &format!("{A}:1-100")), Some(0));
+    }
+
+    #[test]
+    fn test_mysql_gap_tagged() {
+        assert_eq!(
+            mysql_gap(&format!("{A}:1-5:batch:1-4"), &format!("{A}:1-5:batch:1-2")),
+            Some(2)
+        );
+        assert_eq!(mysql_gap(&format!("{A}:1-y"), ""), None);
+    }
+
+    #[test]
+    fn test_mariadb_gap() {
+        assert_eq!(mariadb_gap("0-1-100,1-2-50", "0-1-100,1-2-50"), Some(0));
+        assert_eq!(mariadb_gap("0-1-100,1-2-50", "0-1-90,1-2-45"), Some(15));
+        assert_eq!(mariadb_gap("0-1-100", ""), Some(100));
+        assert_eq!(mariadb_gap("0-1", ""), None);
+    }
+
+    #[test]
+    fn test_missing() {
+        assert_eq!(missing(1, 10, &[]), 10);
+        assert_eq!(missing(1, 10, &[(1, 10)]), 0);
+        assert_eq!(missing(1, 10, &[(3, 4), (8, 20)]), 2 + 3);
+        assert_eq!(missing(5, 6, &[(1, 2), (10, 12)]), 2);
+    }
+}
@@ -0,0 +1,278 @@
+//! Selection of the host a check connects to when the DSN lists several
+//!
+//! Hosts are tried in the listed order, like libpq's `target_session_attrs`:
+//! the first one that accepts the connection and has the requested role is
+//! used. Roles are only probed when a mode other than `any` is requested;
+//! `MySQL` hosts are standbys when `@@read_only` is set.
+
+use super::{CheckError, ErrorKind};
+use crate::{
+    metrics::{SELECTED_HOST, UNREACHABLE_HOSTS},
+    target::{SessionAttrs, Target, host_name},
+};
+use anyhow::{Result, anyhow};
+use dsn::DSN;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    future::Future,
+    sync::{LazyLock, Mutex, PoisonError},
+};
+
+/// Host last selected for each target, by position in the DSN
+static SELECTED: LazyLock<Mutex<HashMap<String, usize>>> = LazyLock::new(Mutex::default);
+
+/// Role of a host, probed after connecting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct HostRole {
+    /// In recovery (`PostgreSQL`) or read-only (`MySQL`)
+    pub(crate) standby: bool,
+    /// New sessions are read-only
+    pub(crate) read_only: bool,
+}
+
+impl HostRole {
+    pub(crate) const fn satisfies(self, attrs: SessionAttrs) -> bool {
+        match attrs {
+            SessionAttrs::Any => true,
+            SessionAttrs::ReadWrite => !self.read_only,
+            SessionAttrs::Primary => !self.standby,
+            SessionAttrs::PreferStandby => self.standby,
+        }
+    }
+}
+
+impl fmt::Display for HostRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.standby, self.read_only) {
+            (true, _) => f.write_str("a standby"),
+            (false, true) => f.write_str("a read-only primary"),
+            (false, false) => f.write_str("a writable primary"),
+        }
+    }
+}
+
+/// Connect to the first listed host with the role requested by the target
+///
+/// `open` connects to a host and probes its role. With `prefer-standby` the
+/// first reachable host is used if none is a standby. If no host matches, the
+/// check fails as `read_only`; if none is reachable, with the last error.
+/// Connections to rejected hosts are closed cleanly.
+pub(crate) async fn connect<'a, C, F, Fut>(target: &'a Target, database: &str, open: F) -> Result<C>
+where
+    C: sqlx::Connection,
+    F: Fn(&'a DSN) -> Fut,
+    Fut: Future<Output = Result<(C, HostRole)>>,
+{
+    let mut rejected = Vec::new();
+    let mut unreachable = 0;
+    let mut last_error = None;
+    let mut reached = false;
+    let mut fallback: Option<(usize, C)> = None;
+    for (index, dsn) in target.hosts().enumerate() {
+        match open(dsn).await {
+            Ok((conn, role)) if role.satisfies(target.session_attrs) => {
+                if let Some((_, unused)) = fallback {
+                    let _ = unused.close().await;
+                }
+                record(target, database, Some(index), unreachable);
+                return Ok(conn);
+            }
+            Ok((conn, role)) => {
+                reached = true;
+                rejected.push(format!("{} is {role}", host_name(dsn)));
+                if target.session_attrs == SessionAttrs::PreferStandby && fallback.is_none() {
+                    fallback = Some((index, conn));
+                } else {
+                    let _ = conn.close().await;
+                }
+            }
+            Err(error) => {
+                rejected.push(format!("{} is unreachable", host_name(dsn)));
+                unreachable += 1;
+                last_error = Some(error);
+            }
+        }
+    }
+    if let Some((index, conn)) = fallback {
+        record(target, database, Some(index), unreachable);
+        return Ok(conn);
+    }
+
+    record(target, database, None, unreachable);
+    let hosts = rejected.len();
+    match last_error {
+        Some(error) if !reached && hosts > 1 => {
+            Err(error.context(format!("none of the {hosts} hosts is reachable")))
+        }
+        Some(error) if !reached => Err(error),
+        _ => Err(CheckError::new(
+            ErrorKind::ReadOnly,
+            anyhow!(
+                "no host is {}: {}",
+                target.session_attrs,
+                rejected.join(", ")
+            ),
+        )
+        .into()),
+    }
+}
+
+/// DSN of the host the last check of a target connected to
+pub(crate) fn selected(target: &Target) -> &DSN {
+    let index = SELECTED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&target.name)
+        .copied();
+    index
+        .and_then(|index| target.hosts().nth(index))
+        .unwrap_or(&target.dsn)
+}
+
+/// Export the selected host and the hosts that could not be reached
+///
+/// Only targets listing several hosts are exported.
+fn record(target: &Target, database: &str, selected: Option<usize>, unreachable: i64) {
+    let name = target.name.as_str();
+    {
+        let mut selections = SELECTED.lock().unwrap_or_else(PoisonError::into_inner);
+        match selected {
+            Some(index) => selections.insert(name.to_string(), index),
+            None => selections.remove(name),
+        };
+    }
+    if target.alternate_hosts.is_empty() {
+        return;
+    }
+
+    UNREACHABLE_HOSTS
+        .with_label_values(&[name, database])
+        .set(unreachable);
+    let mut series = HashSet::new();
+    if let Some(dsn) = selected.and_then(|index| target.hosts().nth(index)) {
+        let host = host_name(dsn);
+        let labels = [name, database, host.as_str()];
+        SELECTED_HOST.vec.with_label_values(&labels).set(1);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    SELECTED_HOST.replace(name, series);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+    use sqlx::{Connection, SqliteConnection};
+
+    const PRIMARY: HostRole = HostRole {
+        standby: false,
+        read_only: false,
+    };
+    const STANDBY: HostRole = HostRole {
+        standby: true,
+        read_only: true,
+    };
+
+    fn target(name: &str, attrs: SessionAttrs) -> Target {
+        Target {
+            session_attrs: attrs,
+            ..Target::for_test(
+                name,
+                "postgres://user@tcp(pg-1:5432,pg-2:5432,pg-3:5432)/db",
+            )
+        }
+    }
+
+    /// Hosts are in-memory databases, `roles` maps a host to its role or unreachable;
+    /// returns the selected host
+    async fn select(target: &Target, roles: &[(&str, Option<HostRole>)]) -> Result<String> {
+        let conn = connect(target, "postgres", |dsn| async move {
+            let host = host_name(dsn);
+            let role = roles
+                .iter()
+                .find(|(name, _)| host.starts_with(name))
+                .and_then(|(_, role)| *role)
+                .ok_or_else(|| {
+                    CheckError::new(ErrorKind::Connection, anyhow!("connection refused"))
+                })?;
+            Ok((SqliteConnection::connect("sqlite::memory:").await?, role))
+        })
+        .await?;
+        conn.close().await?;
+        Ok(host_name(selected(target)))
+    }
+
+    #[tokio::test]
+    async fn test_primary_skips_standbys_and_unreachable() {
+        let target = target("hosts-primary", SessionAttrs::Primary);
+        let roles = [
+            ("pg-1", None),
+            ("pg-2", Some(STANDBY)),
+            ("pg-3", Some(PRIMARY)),
+        ];
+        assert_eq!(select(&target, &roles).await.unwrap(), "pg-3:5432");
+        assert_eq!(host_name(selected(&target)), "pg-3:5432");
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_selected_host_info{database="postgres",host="pg-3:5432",target="hosts-primary"} 1"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_unreachable_hosts{database="postgres",target="hosts-primary"} 1"#
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_prefer_standby_falls_back() {
+        let target = target("hosts-prefer", SessionAttrs::PreferStandby);
+        let roles = [
+            ("pg-1", Some(PRIMARY)),
+            ("pg-2", Some(STANDBY)),
+            ("pg-3", Some(STANDBY)),
+        ];
+        assert_eq!(select(&target, &roles).await.unwrap(), "pg-2:5432");
+
+        let roles = [("pg-1", None), ("pg-2", Some(PRIMARY)), ("pg-3", None)];
+        assert_eq!(select(&target, &roles).await.unwrap(), "pg-2:5432");
+    }
+
+    #[tokio::test]
+    async fn test_no_matching_host() {
+        let target = target("hosts-none", SessionAttrs::ReadWrite);
+        let roles = [
+            ("pg-1", Some(STANDBY)),
+            ("pg-2", None),
+            ("pg-3", Some(PRIMARY)),
+        ];
+        assert_eq!(select(&target, &roles).await.unwrap(), "pg-3:5432");
+
+        let roles = [
+            ("pg-1", Some(STANDBY)),
+            ("pg-2", None),
+            ("pg-3", Some(STANDBY)),
+        ];
+        let error = select(&target, &roles).await.unwrap_err();
+        assert_eq!(ErrorKind::of(&error), ErrorKind::ReadOnly);
+        assert_eq!(
+            error.to_string(),
+            "no host is read-write: pg-1:5432 is a standby, pg-2:5432 is unreachable, pg-3:5432 is a standby"
+        );
+        // the previous selection is forgotten
+        assert_eq!(host_name(selected(&target)), "pg-1:5432");
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.lines().any(|line| {
+            line.starts_with("dbpulse_selected_host_info")
+                && line.contains(r#"target="hosts-none""#)
+        }));
+
+        let error = select(&target, &[]).await.unwrap_err();
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Connection);
+        assert_eq!(
+            format!("{error:#}"),
+            "none of the 3 hosts is reachable: connection refused"
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! `InnoDB` health of `MySQL` and `MariaDB` targets
+//!
+//! Collected best effort on the slow cadence from the `Innodb_%` global
+//! status, the redo log variables, `information_schema.INNODB_METRICS` and
+//! `SHOW ENGINE INNODB STATUS`. The history list length and checkpoint age
+//! come from the engine status when it can be read (it needs the `PROCESS`
+//! privilege), from the `MariaDB` status variables or `INNODB_METRICS`
+//! otherwise.
+
+use crate::metrics::{
+    INNODB_BUFFER_POOL_DIRTY_PAGES, INNODB_BUFFER_POOL_HIT_RATIO, INNODB_BUFFER_POOL_PAGES,
+    INNODB_CHECKPOINT_AGE_BYTES, INNODB_DEADLOCKS, INNODB_HISTORY_LIST_LENGTH,
+    INNODB_LOG_CAPACITY_BYTES, INNODB_ROW_LOCK_CURRENT_WAITS, INNODB_ROW_LOCK_WAITS,
+};
+use prometheus::core::{Atomic, GenericGaugeVec};
+use std::collections::HashMap;
+
+/// `INNODB_METRICS` counters read as fallbacks, enabled by default on `MySQL`
+pub(crate) const INNODB_METRICS: [&str; 2] = ["lock_deadlocks", "trx_rseg_history_len"];
+
+/// Variables sizing the redo log, `innodb_redo_log_capacity` since `MySQL` 8.0.30
+pub(crate) const REDO_LOG_VARIABLES: [&str; 3] = [
+    "innodb_redo_log_capacity",
+    "innodb_log_file_size",
+    "innodb_log_files_in_group",
+];
+
+/// `InnoDB` state seen by a check, `None` where the server does not report it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct InnodbStatus {
+    /// Logical reads served from the buffer pool since the server started
+    pub(crate) buffer_pool_hit_ratio: Option<f64>,
+    pub(crate) buffer_pool_pages: Option<i64>,
+    pub(crate) buffer_pool_dirty_pages: Option<i64>,
+    /// Undo log entries not purged yet
+    pub(crate) history_list_length: Option<i64>,
+    /// Redo log bytes written since the last checkpoint
+    pub(crate) checkpoint_age_bytes: Option<i64>,
+    pub(crate) log_capacity_bytes: Option<i64>,
+    /// Since the server started
+    pub(crate) deadlocks: Option<i64>,
+    /// Since the server started
+    pub(crate) row_lock_waits: Option<i64>,
+    pub(crate) row_lock_current_waits: Option<i64>,
+}
+
+impl InnodbStatus {
+    /// Build the status from status variables, global variables and
+    /// `INNODB_METRICS` counters keyed by lowercase name, and the text of
+    /// `SHOW ENGINE INNODB STATUS`
+    #[must_use]
+    pub(crate) fn parse(values: &HashMap<String, String>, engine_status: Option<&str>) -> Self {
+        let integer = |name: &str| values.get(name).and_then(|value| value.trim().parse().ok());
+        let float = |name: &str| {
+            values
+                .get(name)
+                .and_then(|value| value.trim().parse::<f64>().ok())
+        };
+        let engine = engine_status.map(EngineStatus::parse).unwrap_or_default();
+
+        let buffer_pool_hit_ratio = match (
+            float("innodb_buffer_pool_read_requests"),
+            float("innodb_buffer_pool_reads"),
+        ) {
+            (Some(requests), Some(reads)) if requests > 0.0 => Some(1.0 - reads / requests),
+            _ => None,
+        };
+        let log_capacity_bytes = integer("innodb_redo_log_capacity").or_else(|| {
+            integer("innodb_log_file_size")
+                .map(|size: i64| size * integer("innodb_log_files_in_group").unwrap_or(1))
+        });
+
+        Self {
+            buffer_pool_hit_ratio,
+            buffer_pool_pages: integer("innodb_buffer_pool_pages_total"),
+            buffer_pool_dirty_pages: integer("innodb_buffer_pool_pages_dirty"),
+            history_list_length: engine
+                .history_list_length
+                .or_else(|| integer("innodb_history_list_length"))
+                .or_else(|| integer("trx_rseg_history_len")),
+            checkpoint_age_bytes: engine
+                .checkpoint_age()
+                .or_else(|| integer("innodb_checkpoint_age")),
+            log_capacity_bytes,
+            deadlocks: integer("innodb_deadlocks").or_else(|| integer("lock_deadlocks")),
+            row_lock_waits: integer("innodb_row_lock_waits"),
+            row_lock_current_waits: integer("innodb_row_lock_current_waits"),
+        }
+    }
+}
+
+/// Values read from the text of `SHOW ENGINE INNODB STATUS`
+#[derive(Debug, Default)]
+struct EngineStatus {
+    history_list_length: Option<i64>,
+    log_sequence_number: Option<i64>,
+    last_checkpoint: Option<i64>,
+}
+
+impl EngineStatus {
+    fn parse(text: &str) -> Self {
+        let mut status = Self::default();
+        for line in text.lines() {
+            let line = line.trim();
+            let value = || line.split_whitespace().last()?.parse().ok();
+            if line.starts_with("History list length") {
+                status.history_list_length = value();
+            } else if line.starts_with("Log sequence number") {
+                status.log_sequence_number = value();
+            } else if line.starts_with("Last checkpoint at") {
+                status.last_checkpoint = value();
+            }
+        }
+        status
+    }
+
+    fn checkpoint_age(&self) -> Option<i64> {
+        Some(self.log_sequence_number? - self.last_checkpoint?)
+    }
+}
+
+/// Export a status as metrics, removing the gauges the server did not report
+pub(crate) fn record(target: &str, database: &str, status: &InnodbStatus) {
+    let labels = [target, database];
+    set_or_remove(
+        &*INNODB_BUFFER_POOL_HIT_RATIO,
+        &labels,
+        status.buffer_pool_hit_ratio,
+    );
+    for (gauge, value) in [
+        (&INNODB_BUFFER_POOL_PAGES, status.buffer_pool_pages),
+        (
+            &INNODB_BUFFER_POOL_DIRTY_PAGES,
+            status.buffer_pool_dirty_pages,
+        ),
+        (&INNODB_HISTORY_LIST_LENGTH, status.history_list_length),
+        (&INNODB_CHECKPOINT_AGE_BYTES, status.checkpoint_age_bytes),
+        (&INNODB_LOG_CAPACITY_BYTES, status.log_capacity_bytes),
+        (&INNODB_DEADLOCKS, status.deadlocks),
+        (&INNODB_ROW_LOCK_WAITS, status.row_lock_waits),
+        (
+            &INNODB_ROW_LOCK_CURRENT_WAITS,
+            status.row_lock_current_waits,
+        ),
+    ] {
+        set_or_remove(&**gauge, &labels, value);
+    }
+}
+
+fn set_or_remove<P: Atomic>(gauge: &GenericGaugeVec<P>, labels: &[&str], value: Option<P::T>) {
+    match value {
+        Some(value) => gauge.with_label_values(labels).set(value),
+        None => {
+            let _ = gauge.remove_label_values(labels);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+
+    const ENGINE_STATUS: &str = "
+=====================================
+2026-10-16 10:00:00 0x7f INNODB MONITOR OUTPUT
+=====================================
+------------
+TRANSACTIONS
+------------
+Trx id counter 1234567
+Purge done for trx's n:o < 1234000 undo n:o < 0 state: running but idle
+History list length 4821
+---
+LOG
+---
+Log sequence number          19323644
+Log buffer assigned up to    19323644
+Log flushed up to            19323644
+Pages flushed up to          19000000
+Last checkpoint at           19000000
+";
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_mysql() {
+        let status = InnodbStatus::parse(
+            &values(&[
+                ("innodb_buffer_pool_read_requests", "1000"),
+                ("innodb_buffer_pool_reads", "10"),
+                ("innodb_buffer_pool_pages_total", "8192"),
+                ("innodb_buffer_pool_pages_dirty", "12"),
+                ("innodb_redo_log_capacity", "104857600"),
+                ("innodb_log_file_size", "50331648"),
+                ("innodb_row_lock_waits", "7"),
+                ("innodb_row_lock_current_waits", "1"),
+                ("lock_deadlocks", "3"),
+                ("trx_rseg_history_len", "4000"),
+            ]),
+            Some(ENGINE_STATUS),
+        );
+        assert_eq!(status.buffer_pool_hit_ratio, Some(0.99));
+        assert_eq!(status.buffer_pool_dirty_pages, Some(12));
+        // the engine status is more current than INNODB_METRICS
+        assert_eq!(status.history_list_length, Some(4821));
+        assert_eq!(status.checkpoint_age_bytes, Some(323_644));
+        assert_eq!(status.log_capacity_bytes, Some(104_857_600));
+        assert_eq!(status.deadlocks, Some(3));
+        assert_eq!(status.row_lock_waits, Some(7));
+    }
+
+    #[test]
+    fn test_parse_mariadb_without_engine_status() {
+        let status = InnodbStatus::parse(
+            &values(&[
+                ("innodb_buffer_pool_read_requests", "0"),
+                ("innodb_buffer_pool_reads", "0"),
+                ("innodb_history_list_length", "12"),
+                ("innodb_checkpoint_age", "2048"),
+                ("innodb_log_file_size", "100663296"),
+                ("innodb_deadlocks", "5"),
+                ("lock_deadlocks", "0"),
+            ]),
+            None,
+        );
+        assert_eq!(status.buffer_pool_hit_ratio, None);
+        assert_eq!(status.history_list_length, Some(12));
+        assert_eq!(status.checkpoint_age_bytes, Some(2048));
+        assert_eq!(status.log_capacity_bytes, Some(100_663_296));
+        assert_eq!(status.deadlocks, Some(5));
+        assert_eq!(status.row_lock_waits, None);
+    }
+
+    #[test]
+    fn test_log_capacity_from_log_files() {
+        let status = InnodbStatus::parse(
+            &values(&[
+                ("innodb_log_file_size", "50331648"),
+                ("innodb_log_files_in_group", "2"),
+            ]),
+            None,
+        );
+        assert_eq!(status.log_capacity_bytes, Some(100_663_296));
+    }
+
+    #[test]
+    fn test_record_removes_unreported() {
+        let reported = InnodbStatus {
+            history_list_length: Some(4821),
+            buffer_pool_hit_ratio: Some(0.5),
+            ..InnodbStatus::default()
+        };
+        record("innodb-db", "mysql", &reported);
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_innodb_history_list_length{database="mysql",target="innodb-db"} 4821"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_innodb_buffer_pool_hit_ratio{database="mysql",target="innodb-db"} 0.5"#
+        ));
+
+        record("innodb-db", "mysql", &InnodbStatus::default());
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"target="innodb-db""#));
+    }
+}
@@ -0,0 +1,168 @@
+//! Lock waits, long transactions and the sessions blocking others
+//!
+//! Each check of a writable target takes a snapshot before the read/write
+//! test. It is exported as metrics and in `/status`; blockers are grouped by
+//! user and client so the metric cardinality stays bounded. When the test
+//! itself times out waiting for a lock, the sessions holding locks are named
+//! in the error message.
+
+use crate::{
+    metrics::{BLOCKING_QUERIES, LOCK_BLOCKERS, OLDEST_BLOCKED_QUERY, OLDEST_TRANSACTION},
+    status::STATUS,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write as _,
+};
+
+/// Blockers kept in a report and exported as series, the ones blocking most first
+pub(crate) const MAX_BLOCKERS: i64 = 5;
+
+/// Lock waits and open transactions seen by a check
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LockReport {
+    pub time: DateTime<Utc>,
+    /// Sessions waiting for a lock held by another one
+    pub blocked_sessions: i64,
+    /// How long the oldest of them has been waiting
+    pub oldest_blocked_seconds: Option<f64>,
+    /// Age of the oldest open transaction, the test connection excluded
+    pub oldest_transaction_seconds: Option<f64>,
+    pub blockers: Vec<Blocker>,
+}
+
+/// A session holding a lock others wait for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Blocker {
+    /// `PostgreSQL` backend pid or `MySQL` connection id
+    pub pid: i64,
+    pub user: Option<String>,
+    /// `application_name` on `PostgreSQL`, the client host on `MySQL`
+    pub client: Option<String>,
+    /// Sessions waiting for it, 0 for lock holders listed after a timeout
+    pub blocked_sessions: i64,
+    pub transaction_seconds: Option<f64>,
+}
+
+impl Blocker {
+    fn describe(&self) -> String {
+        let mut description = format!("pid {}", self.pid);
+        let details: Vec<String> = [
+            self.user.as_deref().map(|user| format!("user {user}")),
+            self.client
+                .as_deref()
+                .map(|client| format!("client {client}")),
+            self.transaction_seconds
+                .map(|seconds| format!("transaction open for {seconds:.1}s")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !details.is_empty() {
+            let _ = write!(description, " ({})", details.join(", "));
+        }
+        description
+    }
+}
+
+/// Message naming the sessions holding locks, used as context of a lock timeout
+pub(crate) fn held_by(table: &str, holders: &[Blocker]) -> String {
+    let holders: Vec<String> = holders.iter().map(Blocker::describe).collect();
+    format!("lock on {table} held by {}", holders.join(", "))
+}
+
+/// Export a report as metrics and keep it for `/status`
+pub(crate) fn record(target: &str, database: &str, report: LockReport) {
+    let labels = [target, database];
+    BLOCKING_QUERIES
+        .with_label_values(&labels)
+        .set(report.blocked_sessions);
+    OLDEST_BLOCKED_QUERY
+        .with_label_values(&labels)
+        .set(report.oldest_blocked_seconds.unwrap_or_default());
+    OLDEST_TRANSACTION
+        .with_label_values(&labels)
+        .set(report.oldest_transaction_seconds.unwrap_or_default());
+
+    let mut by_client: BTreeMap<[&str; 2], i64> = BTreeMap::new();
+    for blocker in &report.blockers {
+        let key = [
+            blocker.user.as_deref().unwrap_or_default(),
+            blocker.client.as_deref().unwrap_or_default(),
+        ];
+        *by_client.entry(key).or_default() += blocker.blocked_sessions;
+    }
+    let mut series = HashSet::new();
+    for ([user, client], blocked) in by_client {
+        let labels = [target, database, user, client];
+        LOCK_BLOCKERS.vec.with_label_values(&labels).set(blocked);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    LOCK_BLOCKERS.replace(target, series);
+
+    STATUS.record_locks(target, report);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+
+    fn blocker(pid: i64, user: &str, blocked_sessions: i64) -> Blocker {
+        Blocker {
+            pid,
+            user: Some(user.to_string()),
+            client: Some("batch".to_string()),
+            blocked_sessions,
+            transaction_seconds: Some(35.0),
+        }
+    }
+
+    #[test]
+    fn test_held_by() {
+        let anonymous = Blocker {
+            pid: 7,
+            user: None,
+            client: None,
+            blocked_sessions: 0,
+            transaction_seconds: None,
+        };
+        assert_eq!(
+            held_by("dbpulse_rw", &[blocker(42, "etl", 0), anonymous]),
+            "lock on dbpulse_rw held by pid 42 (user etl, client batch, transaction open for 35.0s), pid 7"
+        );
+    }
+
+    #[test]
+    fn test_record_groups_blockers() {
+        STATUS.register("locks-db", "postgres", 30);
+        let report = LockReport {
+            time: Utc::now(),
+            blocked_sessions: 4,
+            oldest_blocked_seconds: Some(12.5),
+            oldest_transaction_seconds: Some(300.0),
+            blockers: vec![blocker(1, "etl", 3), blocker(2, "etl", 1)],
+        };
+        record("locks-db", "postgres", report.clone());
+
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_lock_blocker_sessions{client="batch",database="postgres",target="locks-db",user="etl"} 4"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_oldest_blocked_query_seconds{database="postgres",target="locks-db"} 12.5"#
+        ));
+        assert_eq!(STATUS.get("locks-db").unwrap().locks, Some(report));
+
+        record("locks-db", "postgres", LockReport::default());
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(
+            r#"dbpulse_lock_blocker_sessions{client="batch",database="postgres",target="locks-db""#
+        ));
+        STATUS.remove("locks-db");
+    }
+}
@@ -1,18 +1,115 @@
+pub mod addresses;
+pub mod cadence;
+pub mod cluster;
+pub mod engine;
+pub mod error;
+pub mod flavor;
+pub mod gtid;
+pub mod hosts;
+pub mod innodb;
+pub mod locks;
 pub mod mysql;
+pub mod pgbouncer;
 pub mod postgres;
+pub mod proxysql;
+pub mod replica;
+pub mod session;
+pub mod sqlite;
+pub mod vacuum;
 
-use crate::tls::TlsMetadata;
+pub use error::{CheckError, ErrorKind};
+pub use session::Session;
+
+use crate::{
+    metrics::{DNS_RESOLUTION_FAILURES, OPERATION_DURATION},
+    tls::TlsMetadata,
+};
+use anyhow::{Context, Result, anyhow};
+use dsn::DSN;
+use serde::Serialize;
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+/// A connect or iteration deadline of a target was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadlineExceeded {
+    /// What timed out: `connect` or `iteration`
+    pub operation: &'static str,
+    pub limit: Duration,
+}
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} timed out after {}ms",
+            self.operation,
+            self.limit.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for DeadlineExceeded {}
+
+/// Resolve the host of a DSN, failures are classified as `dns`
+///
+/// The driver would report a failed lookup as a generic io error. Lookups
+/// are timed as the `dns_resolve` operation. Returns every address the host
+/// resolves to, none for Unix sockets.
+async fn resolve_host(
+    target: &str,
+    database: &str,
+    dsn: &DSN,
+    default_port: u16,
+) -> Result<Vec<SocketAddr>> {
+    let Some(host) = dsn.host.as_deref() else {
+        return Ok(Vec::new());
+    };
+    let port = dsn.port.unwrap_or(default_port);
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+
+    let timer = Instant::now();
+    let lookup = tokio::net::lookup_host((host, port))
+        .await
+        .with_context(|| format!("Failed to resolve {host}"))
+        .and_then(|addresses| {
+            let addresses: Vec<SocketAddr> = addresses.collect();
+            if addresses.is_empty() {
+                return Err(anyhow!("{host} did not resolve to any address"));
+            }
+            Ok(addresses)
+        });
+    OPERATION_DURATION
+        .with_label_values(&[target, database, "dns_resolve"])
+        .observe(timer.elapsed().as_secs_f64());
+    lookup.map_err(|error| {
+        DNS_RESOLUTION_FAILURES
+            .with_label_values(&[target, database])
+            .inc();
+        CheckError::new(ErrorKind::Dns, error).into()
+    })
+}
 
 /// Result from database health check including TLS metadata
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct HealthCheckResult {
     /// Database version string
     pub version: String,
+    /// Server flavor fingerprinted from the version, e.g. `mariadb`, `tidb`, `cockroachdb`
+    pub flavor: &'static str,
     /// Database host currently serving the connection (if available)
     pub db_host: Option<String>,
     /// Database uptime in seconds (if available)
     pub uptime_seconds: Option<i64>,
+    /// Replication lag in seconds (replicas only)
+    pub replication_lag_seconds: Option<f64>,
     /// TLS metadata (if TLS is enabled)
+    #[serde(rename = "tls")]
     pub tls_metadata: Option<TlsMetadata>,
 }
 
@@ -22,12 +119,26 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_deadline_exceeded_display() {
+        let error = DeadlineExceeded {
+            operation: "connect",
+            limit: Duration::from_millis(1500),
+        };
+        assert_eq!(error.to_string(), "connect timed out after 1500ms");
+
+        let wrapped = anyhow::Error::new(error).context("Failed to connect");
+        assert_eq!(wrapped.downcast_ref::<DeadlineExceeded>(), Some(&error));
+    }
+
     #[test]
     fn test_health_check_result_without_tls() {
         let result = HealthCheckResult {
             version: "PostgreSQL 15.0".to_string(),
+            flavor: "postgres",
             db_host: Some("db-1".to_string()),
             uptime_seconds: Some(1_000),
+            replication_lag_seconds: None,
             tls_metadata: None,
         };
 
@@ -49,8 +160,10 @@ mod tests {
 
         let result = HealthCheckResult {
             version: "MySQL 8.0.33".to_string(),
+            flavor: "mysql",
             db_host: Some("db-2".to_string()),
             uptime_seconds: Some(42),
+            replication_lag_seconds: None,
             tls_metadata: Some(tls_metadata),
         };
 
@@ -67,8 +180,10 @@ mod tests {
     fn test_health_check_result_clone() {
         let result = HealthCheckResult {
             version: "PostgreSQL 14.5".to_string(),
+            flavor: "postgres",
             db_host: None,
             uptime_seconds: None,
+            replication_lag_seconds: None,
             tls_metadata: None,
         };
 
@@ -82,8 +197,10 @@ mod tests {
     fn test_health_check_result_debug() {
         let result = HealthCheckResult {
             version: "MySQL 8.0".to_string(),
+            flavor: "mysql",
             db_host: None,
             uptime_seconds: None,
+            replication_lag_seconds: None,
             tls_metadata: None,
         };
 
@@ -96,8 +213,10 @@ mod tests {
     fn test_health_check_result_empty_version() {
         let result = HealthCheckResult {
             version: String::new(),
+            flavor: "postgres",
             db_host: None,
             uptime_seconds: None,
+            replication_lag_seconds: None,
             tls_metadata: None,
         };
 
@@ -117,8 +236,10 @@ mod tests {
 
         let result = HealthCheckResult {
             version: "PostgreSQL 13.0 in recovery mode".to_string(),
+            flavor: "postgres",
             db_host: Some("replica-1".to_string()),
             uptime_seconds: Some(900),
+            replication_lag_seconds: None,
             tls_metadata: Some(tls_metadata),
         };
 
@@ -134,8 +255,10 @@ mod tests {
     fn test_health_check_result_mysql_read_only() {
         let result = HealthCheckResult {
             version: "MySQL 8.0.30 read-only".to_string(),
+            flavor: "mysql",
             db_host: None,
             uptime_seconds: None,
+            replication_lag_seconds: None,
             tls_metadata: None,
         };
 
@@ -146,8 +269,10 @@ mod tests {
     fn test_health_check_result_version_with_special_chars() {
         let result = HealthCheckResult {
             version: "PostgreSQL 15.0 (Ubuntu 15.0-1.pgdg22.04+1)".to_string(),
+            flavor: "postgres",
             db_host: None,
             uptime_seconds: None,
+            replication_lag_seconds: None,
             tls_metadata: None,
         };
 
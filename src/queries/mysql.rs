@@ -1,10 +1,27 @@
-use super::HealthCheckResult;
+use super::{
+    CheckError, DeadlineExceeded, ErrorKind, HealthCheckResult, addresses,
+    cadence::{INNODB_CADENCE, SLOW_COLLECTOR_INTERVAL},
+    cluster::{self, GaleraStatus, GroupMember},
+    flavor::{self, Flavor, Probe},
+    gtid,
+    hosts::{self, HostRole},
+    innodb::{self, InnodbStatus},
+    locks::{self, Blocker, LockReport},
+    replica,
+    replica::WrittenRow,
+    resolve_host,
+    session::{Session, Slot},
+};
 use crate::{
+    custom::{ValueType, integer_value},
     metrics::{
-        BLOCKING_QUERIES, CONNECTION_DURATION, DATABASE_SIZE_BYTES, OPERATION_DURATION,
-        REPLICATION_LAG, ROWS_AFFECTED, TABLE_ROWS, TABLE_SIZE_BYTES, TLS_CERT_PROBE_ERRORS,
-        TLS_HANDSHAKE_DURATION,
+        CONNECTION_STATES, CONNECTIONS_BY_STATE, CONNECTIONS_CURRENT, CONNECTIONS_MAX,
+        CONNECTIONS_MAX_USED, DATABASE_SIZE_BYTES, LONG_IDLE_IN_TRANSACTION, OPERATION_DURATION,
+        REPLICA_GTID_GAP, REPLICA_LAST_ERRNO, REPLICA_RELAY_LOG_SPACE_BYTES,
+        REPLICA_THREAD_RUNNING, REPLICATION_LAG, ROWS_AFFECTED, TABLE_ROWS, TABLE_SIZE_BYTES,
+        TLS_CERT_PROBE_ERRORS, TLS_HANDSHAKE_DURATION,
     },
+    target::{Replica, SessionAttrs, Target, Timeouts, host_name},
     tls::{
         TlsConfig, TlsMetadata, TlsMode, TlsProbeProtocol,
         cache::{CertCache, get_cert_metadata_cached},
@@ -17,26 +34,30 @@ use dsn::DSN;
 use rand::Rng;
 use sqlx::{
     ConnectOptions, Connection, Executor, Row,
-    mysql::{MySqlConnectOptions, MySqlConnection, MySqlDatabaseError, MySqlSslMode},
+    error::DatabaseError,
+    mysql::{MySqlConnectOptions, MySqlConnection, MySqlDatabaseError, MySqlRow, MySqlSslMode},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    time::{Duration, Instant},
 };
-use std::time::Instant;
+use tokio::time;
 use uuid::Uuid;
 
 const MYSQL_SSL_DATE_FORMATS: [&str; 2] = ["%b %e %H:%M:%S %Y GMT", "%Y-%m-%d %H:%M:%S"];
 
-/// Test read/write operations on the default table
+/// Test read/write operations on the table configured for the target
 ///
 /// # Errors
 ///
 /// Returns an error if database connection or operations fail
 pub async fn test_rw(
-    dsn: &DSN,
+    target: &Target,
     now: DateTime<Utc>,
-    range: u32,
-    tls: &TlsConfig,
     cert_cache: &CertCache,
 ) -> Result<HealthCheckResult> {
-    test_rw_with_table(dsn, now, range, tls, cert_cache, "dbpulse_rw").await
+    test_rw_with_table(target, now, cert_cache, &target.table).await
 }
 
 /// Test read/write operations on a specified table
@@ -45,53 +66,263 @@ pub async fn test_rw(
 ///
 /// Returns an error if database connection or operations fail
 pub async fn test_rw_with_table(
-    dsn: &DSN,
+    target: &Target,
+    now: DateTime<Utc>,
+    cert_cache: &CertCache,
+    table_name: &str,
+) -> Result<HealthCheckResult> {
+    let mut session = Session::default();
+    let result = check_rw(target, now, cert_cache, table_name, &mut session.mysql).await;
+    session.close().await;
+    result.map_err(classify)
+}
+
+/// Test read/write operations reusing the connection kept in `session`
+///
+/// In persistent connection mode the connection stays open for the next
+/// check; it is replaced when it no longer answers a ping or a check fails.
+///
+/// # Errors
+///
+/// Returns an error if database connection or operations fail
+pub async fn test_rw_in_session(
+    target: &Target,
+    now: DateTime<Utc>,
+    cert_cache: &CertCache,
+    session: &mut Session,
+) -> Result<HealthCheckResult> {
+    check_rw(target, now, cert_cache, &target.table, &mut session.mysql)
+        .await
+        .map_err(classify)
+}
+
+fn classify(error: anyhow::Error) -> anyhow::Error {
+    CheckError::classify(error, server_error_kind, ErrorKind::Query)
+}
+
+async fn check_rw(
+    target: &Target,
     now: DateTime<Utc>,
-    range: u32,
-    tls: &TlsConfig,
     cert_cache: &CertCache,
     table_name: &str,
+    slot: &mut Slot<MySqlConnection>,
 ) -> Result<HealthCheckResult> {
+    let reused = slot
+        .reuse_with_role(target, "mysql", probe_mysql_role)
+        .await;
+    let (mut conn, opened) = if let Some(live) = reused {
+        live
+    } else {
+        let opened = Instant::now();
+        let conn = open_mysql(target).await?;
+        slot.opened(target, "mysql");
+        (conn, opened)
+    };
+    let result = run_mysql_checks(target, &mut conn, now, cert_cache, table_name).await;
+    slot.release(target, "mysql", conn, opened, result.is_ok())
+        .await;
+    result
+}
+
+/// Run the read-only part of the check against every address of the target
+pub(crate) async fn probe_addresses(target: &Target) {
+    addresses::probe(target, "mysql", 3306, |address| {
+        check_mysql_address(target, address, &target.table)
+    })
+    .await;
+}
+
+/// Connect to the host selected by `target_session_attrs` and apply the session timeouts
+async fn open_mysql(target: &Target) -> Result<MySqlConnection> {
+    hosts::connect(target, "mysql", |dsn| open_mysql_host(target, dsn)).await
+}
+
+/// Connect to one of the listed hosts, probing `@@read_only` unless any host will do
+async fn open_mysql_host(target: &Target, dsn: &DSN) -> Result<(MySqlConnection, HostRole)> {
+    let tls = &target.tls;
     ensure_crypto_provider();
     let options = mysql_connect_options(dsn, tls);
-    let conn_start = Instant::now();
-    let mut conn = connect_mysql(&options, dsn, tls).await?;
-    set_mysql_session_timeouts(&mut conn).await?;
-
-    let health_info = fetch_mysql_health_info(&mut conn).await?;
-    if mysql_is_read_only(&mut conn).await? {
-        maybe_record_mysql_replication_lag(&mut conn).await;
-        return mysql_read_only_result(dsn, tls, &mut conn, cert_cache, health_info).await;
-    }
-
-    monitor_mysql_blocking_queries(&mut conn).await;
-    ensure_mysql_table(&mut conn, table_name).await?;
-    let id = mysql_insert_and_verify(&mut conn, now, range, table_name).await?;
-    mysql_transaction_rollback_test(&mut conn, now, table_name).await?;
-    mysql_cleanup_old_records(&mut conn, now, table_name).await;
-    update_mysql_table_rows_metric(&mut conn, table_name).await;
-    maybe_drop_mysql_table_hourly(&mut conn, now, id, table_name).await;
-    update_mysql_size_metrics(&mut conn, table_name).await;
-
-    let tls_metadata = maybe_extract_mysql_tls(dsn, tls, &mut conn, cert_cache).await;
+    let mut conn = time::timeout(
+        target.timeouts.connect,
+        connect_mysql(target.name.as_str(), &options, dsn, tls),
+    )
+    .await
+    .map_err(|_| DeadlineExceeded {
+        operation: "connect",
+        limit: target.timeouts.connect,
+    })??;
+    set_mysql_session_timeouts(&mut conn, target.timeouts).await?;
+    if target.session_attrs == SessionAttrs::Any {
+        return Ok((conn, HostRole::default()));
+    }
+    let role = probe_mysql_role(&mut conn).await?;
+    Ok((conn, role))
+}
+
+/// Role of the host a connection is open to, a standby when `@@read_only` is set
+async fn probe_mysql_role(conn: &mut MySqlConnection) -> Result<HostRole> {
+    let read_only = mysql_is_read_only(conn).await?;
+    Ok(HostRole {
+        standby: read_only,
+        read_only,
+    })
+}
+
+/// Connect to a resolved address with the credentials of the target and read
+/// the table of the read/write test, the part of the check any backend must serve
+async fn check_mysql_address(target: &Target, address: SocketAddr, table_name: &str) -> Result<()> {
+    ensure_crypto_provider();
+    let options = mysql_connect_options(&target.dsn, &addresses::address_tls(&target.tls))
+        .host(&address.ip().to_string())
+        .port(address.port());
+    let mut conn = time::timeout(target.timeouts.connect, options.connect())
+        .await
+        .map_err(|_| DeadlineExceeded {
+            operation: "connect",
+            limit: target.timeouts.connect,
+        })?
+        .map_err(|e| CheckError::classify(e.into(), server_error_kind, ErrorKind::Connection))?;
+    let read = async {
+        set_mysql_session_timeouts(&mut conn, target.timeouts).await?;
+        sqlx::query(&format!("SELECT t1 FROM {table_name} LIMIT 1"))
+            .fetch_optional(&mut conn)
+            .await
+            .context("Failed to read the table")?;
+        anyhow::Ok(())
+    };
+    let result = read.await.map_err(classify);
     let _ = conn.close().await;
-    CONNECTION_DURATION.observe(conn_start.elapsed().as_secs_f64());
+    result
+}
+
+async fn run_mysql_checks(
+    target: &Target,
+    conn: &mut MySqlConnection,
+    now: DateTime<Utc>,
+    cert_cache: &CertCache,
+    table_name: &str,
+) -> Result<HealthCheckResult> {
+    let (name, range) = (target.name.as_str(), target.range);
+
+    let health_info = fetch_mysql_health_info(target, conn).await?;
+    let flavor = health_info.flavor;
+    let mariadb = flavor == Flavor::MariaDb;
+    let lag = if flavor.supports(Probe::ReplicaStatus) {
+        update_mysql_replication_metrics(name, conn, mariadb).await
+    } else {
+        None
+    };
+    if flavor.supports(Probe::Cluster) {
+        let galera = fetch_galera_status(conn).await;
+        cluster::record_galera(name, "mysql", galera.as_ref());
+        if !mariadb && let Some(members) = fetch_group_members(conn).await {
+            cluster::record_group_members(name, "mysql", &members);
+        }
+        if let Some(galera) = &galera {
+            galera.ensure_primary()?;
+        }
+    }
+    if mysql_is_read_only(conn).await? {
+        return mysql_read_only_result(target, conn, cert_cache, health_info, lag).await;
+    }
+
+    if flavor.supports(Probe::LockWaits) {
+        monitor_mysql_locks(name, conn, mariadb).await;
+    }
+    if flavor.supports(Probe::Sessions) {
+        monitor_mysql_connections(name, conn, target.timeouts.idle_in_transaction).await;
+    }
+    ensure_mysql_table(name, conn, table_name).await?;
+    let replicas = replica::connect(target, |replica| {
+        connect_mysql_replica(replica, target.timeouts)
+    })
+    .await;
+    let row = match mysql_insert_and_verify(name, conn, now, range, table_name).await {
+        Ok(row) => row,
+        Err(error) => return Err(explain_mysql_lock_timeout(conn, table_name, error).await),
+    };
+    replica::measure(target, "mysql", row, replicas, |conn| {
+        wait_for_mysql_replica(conn, table_name, row)
+    })
+    .await;
+    if let Err(error) = mysql_transaction_rollback_test(name, conn, now, table_name).await {
+        let error = CheckError::classify(error, server_error_kind, ErrorKind::Transaction);
+        return Err(explain_mysql_lock_timeout(conn, table_name, error).await);
+    }
+    mysql_cleanup_old_records(name, conn, now, table_name).await;
+    maybe_drop_mysql_table_hourly(conn, now, row.id, table_name).await;
+    if flavor.supports(Probe::TableStats) {
+        update_mysql_table_rows_metric(name, conn, table_name).await;
+        update_mysql_size_metrics(name, conn, table_name).await;
+    }
+    if flavor.supports(Probe::Innodb) {
+        maybe_update_innodb_metrics(name, conn).await;
+    }
+
+    let tls_metadata = maybe_extract_mysql_tls(target, conn, cert_cache).await;
 
     Ok(HealthCheckResult {
         version: health_info.version.context("Expected database version")?,
+        flavor: flavor.as_str(),
         db_host: health_info.db_host,
         uptime_seconds: health_info.uptime_seconds,
+        replication_lag_seconds: lag,
         tls_metadata,
     })
 }
 
+/// Run the query of a custom check and return its scalar result
+///
+/// Uses the same connection options, TLS settings and timeouts as the
+/// read/write test.
+///
+/// # Errors
+///
+/// Returns an error if the connection or query fails, the query returns no
+/// row or `NULL`, or the value does not have the expected type
+pub(crate) async fn custom_check_value(
+    target: &Target,
+    sql: &str,
+    value_type: ValueType,
+) -> Result<f64> {
+    let mut conn = open_mysql(target).await?;
+    let value = fetch_mysql_scalar(&mut conn, sql, value_type).await;
+    let _ = conn.close().await;
+    value.map_err(classify)
+}
+
+async fn fetch_mysql_scalar(
+    conn: &mut MySqlConnection,
+    sql: &str,
+    value_type: ValueType,
+) -> Result<f64> {
+    let value = match value_type {
+        ValueType::Integer => sqlx::query_scalar::<_, Option<i64>>(sql)
+            .fetch_one(&mut *conn)
+            .await?
+            .map(integer_value),
+        ValueType::Float => {
+            sqlx::query_scalar::<_, Option<f64>>(sql)
+                .fetch_one(&mut *conn)
+                .await?
+        }
+        // MySQL booleans are integers, any non-zero value is true
+        ValueType::Boolean => sqlx::query_scalar::<_, Option<i64>>(sql)
+            .fetch_one(&mut *conn)
+            .await?
+            .map(|value| if value == 0 { 0.0 } else { 1.0 }),
+    };
+    value.context("Query returned NULL")
+}
+
 struct MySqlHealthInfo {
+    flavor: Flavor,
     version: Option<String>,
     db_host: Option<String>,
     uptime_seconds: Option<i64>,
 }
 
-fn mysql_connect_options(dsn: &DSN, tls: &TlsConfig) -> MySqlConnectOptions {
+pub(crate) fn mysql_connect_options(dsn: &DSN, tls: &TlsConfig) -> MySqlConnectOptions {
     let mut options = MySqlConnectOptions::new()
         .username(dsn.username.clone().unwrap_or_default().as_ref())
         .password(dsn.password.clone().unwrap_or_default().as_str())
@@ -129,27 +360,29 @@ fn mysql_connect_options(dsn: &DSN, tls: &TlsConfig) -> MySqlConnectOptions {
     options
 }
 
-fn record_mysql_connect_metrics(tls: &TlsConfig, connect_timer: Instant) {
+fn record_mysql_connect_metrics(target: &str, tls: &TlsConfig, connect_timer: Instant) {
     let connect_duration = connect_timer.elapsed().as_secs_f64();
     OPERATION_DURATION
-        .with_label_values(&["mysql", "connect"])
+        .with_label_values(&[target, "mysql", "connect"])
         .observe(connect_duration);
     if tls.mode.is_enabled() {
         TLS_HANDSHAKE_DURATION
-            .with_label_values(&["mysql"])
+            .with_label_values(&[target, "mysql"])
             .observe(connect_duration);
     }
 }
 
 async fn connect_mysql(
+    target: &str,
     options: &MySqlConnectOptions,
     dsn: &DSN,
     tls: &TlsConfig,
 ) -> Result<MySqlConnection> {
+    resolve_host(target, "mysql", dsn, 3306).await?;
     let connect_timer = Instant::now();
     match options.connect().await {
         Ok(conn) => {
-            record_mysql_connect_metrics(tls, connect_timer);
+            record_mysql_connect_metrics(target, tls, connect_timer);
             Ok(conn)
         }
         Err(err) => {
@@ -162,10 +395,10 @@ async fn connect_mysql(
                 {
                     create_mysql_database(options, dsn).await?;
                     let conn = options.connect().await?;
-                    record_mysql_connect_metrics(tls, connect_timer);
+                    record_mysql_connect_metrics(target, tls, connect_timer);
                     Ok(conn)
                 } else {
-                    Err(db_err.into())
+                    Err(sqlx::Error::Database(db_err).into())
                 }
             } else {
                 Err(err.into())
@@ -174,6 +407,39 @@ async fn connect_mysql(
     }
 }
 
+/// Category of a MySQL/MariaDB server error, by error number
+pub(crate) fn server_error_kind(error: &dyn DatabaseError) -> Option<ErrorKind> {
+    let number = error.try_downcast_ref::<MySqlDatabaseError>()?.number();
+    Some(match number {
+        // ER_DBACCESS_DENIED_ERROR, ER_ACCESS_DENIED_ERROR, ER_NOT_SUPPORTED_AUTH_MODE,
+        // ER_ACCESS_DENIED_NO_PASSWORD_ERROR
+        1044 | 1045 | 1251 | 1698 => ErrorKind::Authentication,
+        // ER_CON_COUNT_ERROR, ER_TOO_MANY_USER_CONNECTIONS
+        1040 | 1203 => ErrorKind::TooManyConnections,
+        // ER_NORMAL_SHUTDOWN, ER_SERVER_SHUTDOWN, ER_CONNECTION_KILLED
+        1077 | 1053 | 1927 => ErrorKind::AdminShutdown,
+        // ER_DISK_FULL, ER_RECORD_FILE_FULL
+        1021 | 1114 => ErrorKind::DiskFull,
+        1213 => ErrorKind::Deadlock,
+        // ER_LOCK_WAIT_TIMEOUT, ER_QUERY_TIMEOUT, MariaDB ER_STATEMENT_TIMEOUT
+        1205 | 3024 | 1969 => ErrorKind::Timeout,
+        // ER_OPTION_PREVENTS_STATEMENT, ER_CANT_EXECUTE_IN_READ_ONLY_TRANSACTION,
+        // ER_READ_ONLY_MODE
+        1290 | 1792 | 1836 => ErrorKind::ReadOnly,
+        // ER_UNKNOWN_COM_ERROR, returned by Galera nodes outside the primary component;
+        // ER_RUN_HOOK_ERROR, Group Replication members without quorum
+        1047 | 3100 => ErrorKind::Cluster,
+        // CR_UNKNOWN_HOST
+        2005 => ErrorKind::Dns,
+        // CR_CONNECTION_ERROR, CR_CONN_HOST_ERROR, CR_SERVER_GONE_ERROR, CR_SERVER_LOST,
+        // ER_ABORTING_CONNECTION, ER_NET_*
+        2002 | 2003 | 2006 | 2013 | 1152 | 1158..=1161 => ErrorKind::Connection,
+        // ER_ERROR_DURING_COMMIT, ER_ERROR_DURING_ROLLBACK, ER_XA_RBROLLBACK
+        1180 | 1181 | 1402 => ErrorKind::Transaction,
+        _ => return None,
+    })
+}
+
 async fn create_mysql_database(options: &MySqlConnectOptions, dsn: &DSN) -> Result<()> {
     let tmp_options = options.clone().database("mysql");
     let mut tmp_conn = tmp_options.connect().await?;
@@ -186,34 +452,53 @@ async fn create_mysql_database(options: &MySqlConnectOptions, dsn: &DSN) -> Resu
     Ok(())
 }
 
-async fn set_mysql_session_timeouts(conn: &mut MySqlConnection) -> Result<()> {
-    if sqlx::query("SET SESSION max_execution_time = 5000")
-        .execute(&mut *conn)
-        .await
-        .is_err()
+async fn set_mysql_session_timeouts(conn: &mut MySqlConnection, timeouts: Timeouts) -> Result<()> {
+    if sqlx::query(&format!(
+        "SET SESSION max_execution_time = {}",
+        timeouts.statement.as_millis()
+    ))
+    .execute(&mut *conn)
+    .await
+    .is_err()
     {
-        let _ = sqlx::query("SET SESSION max_statement_time = 5")
-            .execute(&mut *conn)
-            .await;
+        // MariaDB uses max_statement_time (seconds, fractional)
+        let _ = sqlx::query(&format!(
+            "SET SESSION max_statement_time = {:.3}",
+            timeouts.statement.as_secs_f64()
+        ))
+        .execute(&mut *conn)
+        .await;
     }
 
-    sqlx::query("SET SESSION innodb_lock_wait_timeout = 2")
-        .execute(&mut *conn)
-        .await
-        .context("Failed to set innodb_lock_wait_timeout")?;
+    // innodb_lock_wait_timeout only accepts whole seconds (minimum 1)
+    let lock_secs = timeouts.lock.as_millis().div_ceil(1000).max(1);
+    sqlx::query(&format!(
+        "SET SESSION innodb_lock_wait_timeout = {lock_secs}"
+    ))
+    .execute(&mut *conn)
+    .await
+    .context("Failed to set innodb_lock_wait_timeout")?;
     Ok(())
 }
 
-async fn fetch_mysql_health_info(conn: &mut MySqlConnection) -> Result<MySqlHealthInfo> {
+async fn fetch_mysql_health_info(
+    target: &Target,
+    conn: &mut MySqlConnection,
+) -> Result<MySqlHealthInfo> {
     let version: Option<String> = sqlx::query_scalar("SELECT VERSION()")
         .fetch_optional(&mut *conn)
         .await
         .context("Failed to fetch database version")?;
-    let db_host: Option<String> = sqlx::query_scalar("SELECT @@hostname")
-        .fetch_optional(&mut *conn)
-        .await
-        .ok()
-        .flatten();
+    let flavor = detect_mysql_flavor(target, conn, version.as_deref().unwrap_or_default()).await;
+    let db_host: Option<String> = if flavor.supports(Probe::Hostname) {
+        sqlx::query_scalar("SELECT @@hostname")
+            .fetch_optional(&mut *conn)
+            .await
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
     let uptime_seconds = sqlx::query("SHOW GLOBAL STATUS LIKE 'Uptime'")
         .fetch_optional(&mut *conn)
         .await
@@ -226,12 +511,41 @@ async fn fetch_mysql_health_info(conn: &mut MySqlConnection) -> Result<MySqlHeal
         });
 
     Ok(MySqlHealthInfo {
+        flavor,
         version,
         db_host,
         uptime_seconds,
     })
 }
 
+/// Flavor of the server, asking for `@@version_comment` and `@@aurora_version`
+/// only when `VERSION()` is not conclusive and the host or version changed
+async fn detect_mysql_flavor(target: &Target, conn: &mut MySqlConnection, version: &str) -> Flavor {
+    let flavor = Flavor::detect(version, None, None);
+    if flavor != Flavor::MySql {
+        return flavor;
+    }
+    let host = host_name(hosts::selected(target));
+    if let Some(flavor) = flavor::DETECTED.get(&target.name, &host, version) {
+        return flavor;
+    }
+
+    let comment: Option<String> = sqlx::query_scalar("SELECT @@version_comment")
+        .fetch_optional(&mut *conn)
+        .await
+        .ok()
+        .flatten();
+    // only Aurora has the variable, other servers reject the query
+    let aurora_version: Option<String> = sqlx::query_scalar("SELECT @@aurora_version")
+        .fetch_optional(&mut *conn)
+        .await
+        .ok()
+        .flatten();
+    let flavor = Flavor::detect(version, comment.as_deref(), aurora_version.as_deref());
+    flavor::DETECTED.insert(&target.name, &host, version, flavor);
+    flavor
+}
+
 async fn mysql_is_read_only(conn: &mut MySqlConnection) -> Result<bool> {
     let row = sqlx::query("SELECT @@read_only;")
         .fetch_one(&mut *conn)
@@ -247,53 +561,447 @@ async fn mysql_is_read_only(conn: &mut MySqlConnection) -> Result<bool> {
     ))
 }
 
-async fn maybe_record_mysql_replication_lag(conn: &mut MySqlConnection) {
-    if let Ok(Some(row)) = sqlx::query("SHOW REPLICA STATUS")
+/// State of a replication channel, from a row of `SHOW REPLICA STATUS`
+///
+/// `MySQL` 8.0.22 renamed the `Slave`/`Master` columns to `Replica`/`Source`;
+/// `MariaDB` still uses the old names and calls channels connections.
+#[derive(Debug, PartialEq, Eq)]
+struct ReplicaChannel {
+    channel: String,
+    io_running: bool,
+    sql_running: bool,
+    last_io_errno: i64,
+    last_sql_errno: i64,
+    relay_log_space: Option<i64>,
+    lag_seconds: Option<i64>,
+    gtid_gap: Option<u64>,
+}
+
+impl ReplicaChannel {
+    fn parse(column: impl Fn(&str) -> Option<String>) -> Self {
+        let first = |names: &[&str]| names.iter().find_map(|name| column(name));
+        let number = |names: &[&str]| first(names).and_then(|value| value.trim().parse().ok());
+        let running = |names: &[&str]| first(names).is_some_and(|value| value == "Yes");
+        let non_empty = |name: &str| column(name).filter(|value| !value.trim().is_empty());
+
+        let gtid_gap = if let Some(retrieved) = non_empty("Retrieved_Gtid_Set") {
+            gtid::mysql_gap(&retrieved, &column("Executed_Gtid_Set").unwrap_or_default())
+        } else if let Some(io_pos) = non_empty("Gtid_IO_Pos") {
+            gtid::mariadb_gap(&io_pos, &column("Gtid_Slave_Pos").unwrap_or_default())
+        } else {
+            None
+        };
+
+        Self {
+            channel: first(&["Channel_Name", "Connection_name"]).unwrap_or_default(),
+            io_running: running(&["Replica_IO_Running", "Slave_IO_Running"]),
+            sql_running: running(&["Replica_SQL_Running", "Slave_SQL_Running"]),
+            last_io_errno: number(&["Last_IO_Errno"]).unwrap_or_default(),
+            last_sql_errno: number(&["Last_SQL_Errno"]).unwrap_or_default(),
+            relay_log_space: number(&["Relay_Log_Space"]),
+            lag_seconds: number(&["Seconds_Behind_Source", "Seconds_Behind_Master"])
+                .filter(|lag| *lag >= 0),
+            gtid_gap,
+        }
+    }
+}
+
+/// Column of a status row as text, whatever type the server reports it with
+pub(crate) fn mysql_column_text(row: &MySqlRow, name: &str) -> Option<String> {
+    if let Ok(value) = row.try_get::<Option<String>, _>(name) {
+        return value;
+    }
+    if let Ok(value) = row.try_get::<Option<Vec<u8>>, _>(name) {
+        return value.map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+    }
+    if let Ok(value) = row.try_get::<Option<i64>, _>(name) {
+        return value.map(|value| value.to_string());
+    }
+    row.try_get::<Option<u64>, _>(name)
+        .ok()
+        .flatten()
+        .map(|value| value.to_string())
+}
+
+async fn fetch_mysql_replica_channels(
+    conn: &mut MySqlConnection,
+    mariadb: bool,
+) -> Option<Vec<ReplicaChannel>> {
+    // MariaDB lists every multi-source connection only with SHOW ALL SLAVES
+    // STATUS, MySQL before 8.0.22 only knows SHOW SLAVE STATUS
+    let statements: &[&str] = if mariadb {
+        &["SHOW ALL SLAVES STATUS"]
+    } else {
+        &["SHOW REPLICA STATUS", "SHOW SLAVE STATUS"]
+    };
+    for statement in statements {
+        if let Ok(rows) = sqlx::query(statement).fetch_all(&mut *conn).await {
+            return Some(
+                rows.iter()
+                    .map(|row| ReplicaChannel::parse(|name| mysql_column_text(row, name)))
+                    .collect(),
+            );
+        }
+    }
+    None
+}
+
+/// Record the state of every replication channel, returns the highest lag
+///
+/// A server without channels is a primary and its channel series are
+/// removed; if the status cannot be read (missing privilege) they are kept.
+async fn update_mysql_replication_metrics(
+    target: &str,
+    conn: &mut MySqlConnection,
+    mariadb: bool,
+) -> Option<f64> {
+    let channels = fetch_mysql_replica_channels(conn, mariadb).await?;
+
+    let (mut threads, mut relay_logs, mut gaps) = (HashSet::new(), HashSet::new(), HashSet::new());
+    for status in &channels {
+        let channel = status.channel.as_str();
+        for (thread, running, errno) in [
+            ("io", status.io_running, status.last_io_errno),
+            ("sql", status.sql_running, status.last_sql_errno),
+        ] {
+            let labels = [target, "mysql", channel, thread];
+            REPLICA_THREAD_RUNNING
+                .vec
+                .with_label_values(&labels)
+                .set(i64::from(running));
+            REPLICA_LAST_ERRNO.vec.with_label_values(&labels).set(errno);
+            threads.insert(labels.map(str::to_string).to_vec());
+        }
+        let labels = [target, "mysql", channel];
+        if let Some(space) = status.relay_log_space {
+            REPLICA_RELAY_LOG_SPACE_BYTES
+                .vec
+                .with_label_values(&labels)
+                .set(space);
+            relay_logs.insert(labels.map(str::to_string).to_vec());
+        }
+        if let Some(gap) = status.gtid_gap {
+            REPLICA_GTID_GAP
+                .vec
+                .with_label_values(&labels)
+                .set(i64::try_from(gap).unwrap_or(i64::MAX));
+            gaps.insert(labels.map(str::to_string).to_vec());
+        }
+    }
+    REPLICA_THREAD_RUNNING.replace(target, threads.clone());
+    REPLICA_LAST_ERRNO.replace(target, threads);
+    REPLICA_RELAY_LOG_SPACE_BYTES.replace(target, relay_logs);
+    REPLICA_GTID_GAP.replace(target, gaps);
+
+    let lag_seconds = channels
+        .iter()
+        .filter_map(|status| status.lag_seconds)
+        .max()?;
+    let lag = f64::from(i32::try_from(lag_seconds).unwrap_or(i32::MAX));
+    REPLICATION_LAG
+        .with_label_values(&[target, "mysql"])
+        .observe(lag);
+    Some(lag)
+}
+
+/// Status of the Galera node, `None` if the server is not part of a cluster
+async fn fetch_galera_status(conn: &mut MySqlConnection) -> Option<GaleraStatus> {
+    let wsrep_on = sqlx::query("SHOW GLOBAL VARIABLES LIKE 'wsrep_on'")
         .fetch_optional(&mut *conn)
         .await
-        && let Ok(lag_seconds) = row.try_get::<i64, _>("Seconds_Behind_Source")
-        && lag_seconds >= 0
+        .ok()
+        .flatten()?;
+    if !mysql_column_text(&wsrep_on, "Value").is_some_and(|value| value.eq_ignore_ascii_case("ON"))
     {
-        let lag_i32 = i32::try_from(lag_seconds).unwrap_or(i32::MAX);
-        REPLICATION_LAG
-            .with_label_values(&["mysql"])
-            .observe(f64::from(lag_i32));
+        return None;
     }
+
+    let rows = sqlx::query(r"SHOW GLOBAL STATUS LIKE 'wsrep\_%'")
+        .fetch_all(&mut *conn)
+        .await
+        .ok()?;
+    let status: HashMap<String, String> = rows
+        .iter()
+        .filter_map(|row| {
+            Some((
+                mysql_column_text(row, "Variable_name")?,
+                mysql_column_text(row, "Value")?,
+            ))
+        })
+        .collect();
+    Some(GaleraStatus::parse(&status))
+}
+
+/// Members of the replication group, empty without Group Replication
+///
+/// `None` if they cannot be read, e.g. `MEMBER_ROLE` is missing before 8.0.2.
+async fn fetch_group_members(conn: &mut MySqlConnection) -> Option<Vec<GroupMember>> {
+    let rows = sqlx::query(
+        "SELECT MEMBER_ID, MEMBER_HOST, MEMBER_PORT, MEMBER_STATE, MEMBER_ROLE \
+         FROM performance_schema.replication_group_members",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .ok()?;
+
+    Some(
+        rows.iter()
+            .filter_map(|row| {
+                let id = mysql_column_text(row, "MEMBER_ID").filter(|id| !id.is_empty())?;
+                let host = mysql_column_text(row, "MEMBER_HOST").unwrap_or_default();
+                let member = if host.is_empty() {
+                    id
+                } else {
+                    mysql_column_text(row, "MEMBER_PORT")
+                        .map_or_else(|| host.clone(), |port| format!("{host}:{port}"))
+                };
+                Some(GroupMember {
+                    member,
+                    state: mysql_column_text(row, "MEMBER_STATE").unwrap_or_default(),
+                    role: mysql_column_text(row, "MEMBER_ROLE").unwrap_or_default(),
+                })
+            })
+            .collect(),
+    )
 }
 
 async fn mysql_read_only_result(
-    dsn: &DSN,
-    tls: &TlsConfig,
+    target: &Target,
     conn: &mut MySqlConnection,
     cert_cache: &CertCache,
     health_info: MySqlHealthInfo,
+    replication_lag_seconds: Option<f64>,
 ) -> Result<HealthCheckResult> {
-    let tls_metadata = maybe_extract_mysql_tls(dsn, tls, conn, cert_cache).await;
+    let tls_metadata = maybe_extract_mysql_tls(target, conn, cert_cache).await;
     Ok(HealthCheckResult {
         version: format!(
             "{} - Database is in read-only mode",
             health_info.version.unwrap_or_default()
         ),
+        flavor: health_info.flavor.as_str(),
         db_host: health_info.db_host,
         uptime_seconds: health_info.uptime_seconds,
+        replication_lag_seconds,
         tls_metadata,
     })
 }
 
-async fn monitor_mysql_blocking_queries(conn: &mut MySqlConnection) {
-    if let Ok(Some(blocking_count)) = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM information_schema.processlist WHERE state LIKE '%lock%' OR state LIKE '%Locked%'",
+/// Snapshot `InnoDB` lock waits, the oldest transaction and the top blockers
+async fn monitor_mysql_locks(target: &str, conn: &mut MySqlConnection, mariadb: bool) {
+    let Ok((blocked_sessions, oldest_blocked, oldest_transaction)) =
+        sqlx::query_as::<_, (i64, Option<i64>, Option<i64>)>(
+            r"
+            SELECT CAST(COALESCE(SUM(trx_state = 'LOCK WAIT'), 0) AS SIGNED),
+                   MAX(IF(trx_state = 'LOCK WAIT',
+                          TIMESTAMPDIFF(SECOND, trx_wait_started, NOW()), NULL)),
+                   MAX(TIMESTAMPDIFF(SECOND, trx_started, NOW()))
+            FROM information_schema.innodb_trx
+            WHERE trx_mysql_thread_id <> CONNECTION_ID()
+            ",
+        )
+        .fetch_one(&mut *conn)
+        .await
+    else {
+        return;
+    };
+
+    // performance_schema.data_lock_waits replaced information_schema.innodb_lock_waits
+    // in MySQL 8.0, MariaDB only has the latter
+    let sources: &[(&str, &str, &str)] = if mariadb {
+        &[(
+            "information_schema.innodb_lock_waits",
+            "requesting_trx_id",
+            "blocking_trx_id",
+        )]
+    } else {
+        &[
+            (
+                "performance_schema.data_lock_waits",
+                "REQUESTING_ENGINE_TRANSACTION_ID",
+                "BLOCKING_ENGINE_TRANSACTION_ID",
+            ),
+            (
+                "information_schema.innodb_lock_waits",
+                "requesting_trx_id",
+                "blocking_trx_id",
+            ),
+        ]
+    };
+    let mut blockers = Vec::new();
+    for (waits, requesting, blocking) in sources {
+        let sql = format!(
+            r"
+            SELECT CAST(b.trx_mysql_thread_id AS SIGNED),
+                   p.USER,
+                   SUBSTRING_INDEX(p.HOST, ':', 1),
+                   COUNT(DISTINCT w.{requesting}) AS blocked,
+                   TIMESTAMPDIFF(SECOND, b.trx_started, NOW())
+            FROM {waits} w
+            JOIN information_schema.innodb_trx b ON b.trx_id = w.{blocking}
+            LEFT JOIN information_schema.processlist p ON p.ID = b.trx_mysql_thread_id
+            GROUP BY b.trx_mysql_thread_id, p.USER, p.HOST, b.trx_started
+            ORDER BY blocked DESC, b.trx_started
+            LIMIT ?
+            "
+        );
+        if let Ok(rows) = sqlx::query_as(&sql)
+            .bind(locks::MAX_BLOCKERS)
+            .fetch_all(&mut *conn)
+            .await
+        {
+            blockers = rows.into_iter().map(mysql_blocker).collect();
+            break;
+        }
+    }
+
+    locks::record(
+        target,
+        "mysql",
+        LockReport {
+            time: Utc::now(),
+            blocked_sessions,
+            oldest_blocked_seconds: oldest_blocked.map(seconds),
+            oldest_transaction_seconds: oldest_transaction.map(seconds),
+            blockers,
+        },
+    );
+}
+
+fn seconds(value: i64) -> f64 {
+    f64::from(i32::try_from(value).unwrap_or(i32::MAX))
+}
+
+fn mysql_blocker(
+    (pid, user, client, blocked_sessions, transaction_seconds): (
+        i64,
+        Option<String>,
+        Option<String>,
+        i64,
+        Option<i64>,
+    ),
+) -> Blocker {
+    Blocker {
+        pid,
+        user,
+        client,
+        blocked_sessions,
+        transaction_seconds: transaction_seconds.map(seconds),
+    }
+}
+
+/// Name the transactions holding row locks if `error` is a lock wait timeout
+async fn explain_mysql_lock_timeout(
+    conn: &mut MySqlConnection,
+    table_name: &str,
+    error: anyhow::Error,
+) -> anyhow::Error {
+    let lock_timeout = error.chain().any(|cause| {
+        cause
+            .downcast_ref::<sqlx::Error>()
+            .and_then(sqlx::Error::as_database_error)
+            .and_then(|error| error.try_downcast_ref::<MySqlDatabaseError>())
+            .is_some_and(|error| error.number() == 1205)
+    });
+    if !lock_timeout {
+        return error;
+    }
+
+    let holders: Vec<Blocker> = sqlx::query_as(
+        r"
+        SELECT CAST(t.trx_mysql_thread_id AS SIGNED),
+               p.USER,
+               SUBSTRING_INDEX(p.HOST, ':', 1),
+               CAST(0 AS SIGNED),
+               TIMESTAMPDIFF(SECOND, t.trx_started, NOW())
+        FROM information_schema.innodb_trx t
+        LEFT JOIN information_schema.processlist p ON p.ID = t.trx_mysql_thread_id
+        WHERE t.trx_rows_locked > 0 AND t.trx_mysql_thread_id <> CONNECTION_ID()
+        ORDER BY t.trx_started
+        LIMIT ?
+        ",
     )
-    .fetch_optional(&mut *conn)
+    .bind(locks::MAX_BLOCKERS)
+    .fetch_all(&mut *conn)
+    .await
+    .map(|rows| rows.into_iter().map(mysql_blocker).collect())
+    .unwrap_or_default();
+    if holders.is_empty() {
+        return error;
+    }
+    error.context(locks::held_by(table_name, &holders))
+}
+
+async fn monitor_mysql_connections(
+    target: &str,
+    conn: &mut MySqlConnection,
+    idle_threshold: Duration,
+) {
+    let labels = [target, "mysql"];
+    if let Ok(rows) = sqlx::query(
+        "SHOW GLOBAL STATUS WHERE Variable_name IN ('Threads_connected', 'Max_used_connections')",
+    )
+    .fetch_all(&mut *conn)
     .await
     {
-        BLOCKING_QUERIES
-            .with_label_values(&["mysql"])
-            .set(blocking_count);
+        for row in &rows {
+            let gauge = match mysql_column_text(row, "Variable_name").as_deref() {
+                Some("Threads_connected") => &CONNECTIONS_CURRENT,
+                Some("Max_used_connections") => &CONNECTIONS_MAX_USED,
+                _ => continue,
+            };
+            if let Some(value) =
+                mysql_column_text(row, "Value").and_then(|value| value.parse().ok())
+            {
+                gauge.with_label_values(&labels).set(value);
+            }
+        }
+    }
+    if let Ok(max) = sqlx::query_scalar::<_, i64>("SELECT CAST(@@max_connections AS SIGNED)")
+        .fetch_one(&mut *conn)
+        .await
+    {
+        CONNECTIONS_MAX.with_label_values(&labels).set(max);
+    }
+
+    // a sleeping thread with an InnoDB transaction is idle in transaction,
+    // TIME is the number of seconds since its last statement
+    if let Ok((active, sleeping, idle_in_transaction, long_idle)) =
+        sqlx::query_as::<_, (i64, i64, i64, i64)>(
+            r"
+            SELECT CAST(COALESCE(SUM(p.COMMAND <> 'Sleep'), 0) AS SIGNED),
+                   CAST(COALESCE(SUM(p.COMMAND = 'Sleep'), 0) AS SIGNED),
+                   CAST(COALESCE(SUM(p.COMMAND = 'Sleep' AND t.trx_id IS NOT NULL), 0) AS SIGNED),
+                   CAST(COALESCE(SUM(p.COMMAND = 'Sleep' AND t.trx_id IS NOT NULL
+                                     AND p.TIME >= ?), 0) AS SIGNED)
+            FROM information_schema.processlist p
+            LEFT JOIN information_schema.innodb_trx t ON t.trx_mysql_thread_id = p.ID
+            WHERE p.COMMAND NOT IN ('Daemon', 'Binlog Dump', 'Binlog Dump GTID')
+            ",
+        )
+        .bind(idle_threshold.as_secs())
+        .fetch_one(&mut *conn)
+        .await
+    {
+        LONG_IDLE_IN_TRANSACTION
+            .with_label_values(&labels)
+            .set(long_idle);
+        for (state, count) in CONNECTION_STATES.into_iter().zip([
+            active,
+            sleeping - idle_in_transaction,
+            idle_in_transaction,
+        ]) {
+            CONNECTIONS_BY_STATE
+                .with_label_values(&[target, "mysql", state])
+                .set(count);
+        }
     }
 }
 
-async fn ensure_mysql_table(conn: &mut MySqlConnection, table_name: &str) -> Result<()> {
+async fn ensure_mysql_table(
+    target: &str,
+    conn: &mut MySqlConnection,
+    table_name: &str,
+) -> Result<()> {
     let create_table_sql = format!(
         r"
         CREATE TABLE IF NOT EXISTS {table_name} (
@@ -310,17 +1018,18 @@ async fn ensure_mysql_table(conn: &mut MySqlConnection, table_name: &str) -> Res
     let create_table_timer = Instant::now();
     conn.execute(create_table_sql.as_str()).await?;
     OPERATION_DURATION
-        .with_label_values(&["mysql", "create_table"])
+        .with_label_values(&[target, "mysql", "create_table"])
         .observe(create_table_timer.elapsed().as_secs_f64());
     Ok(())
 }
 
 async fn mysql_insert_and_verify(
+    target: &str,
     conn: &mut MySqlConnection,
     now: DateTime<Utc>,
     range: u32,
     table_name: &str,
-) -> Result<u32> {
+) -> Result<WrittenRow> {
     let id: u32 = rand::rng().random_range(0..range);
     let uuid = Uuid::new_v4();
 
@@ -339,11 +1048,12 @@ async fn mysql_insert_and_verify(
         .bind(uuid.to_string())
         .execute(&mut *conn)
         .await?;
+    let committed = Instant::now();
     OPERATION_DURATION
-        .with_label_values(&["mysql", "insert"])
+        .with_label_values(&[target, "mysql", "insert"])
         .observe(insert_timer.elapsed().as_secs_f64());
     ROWS_AFFECTED
-        .with_label_values(&["mysql", "insert"])
+        .with_label_values(&[target, "mysql", "insert"])
         .inc_by(insert_result.rows_affected());
 
     let select_sql = format!(
@@ -360,7 +1070,7 @@ async fn mysql_insert_and_verify(
         .await
         .context("Failed to query the database")?;
     OPERATION_DURATION
-        .with_label_values(&["mysql", "select"])
+        .with_label_values(&[target, "mysql", "select"])
         .observe(select_timer.elapsed().as_secs_f64());
 
     let (t1, v4) = row.context("Expected records")?;
@@ -374,10 +1084,45 @@ async fn mysql_insert_and_verify(
         ));
     }
 
-    Ok(id)
+    Ok(WrittenRow {
+        id,
+        uuid,
+        committed,
+    })
+}
+
+/// Connect to a replica, before the row it is polled for is written
+async fn connect_mysql_replica(replica: &Replica, timeouts: Timeouts) -> Result<MySqlConnection> {
+    let options = mysql_connect_options(&replica.dsn, &replica.tls);
+    let mut conn = options.connect().await.context("Failed to connect")?;
+    set_mysql_session_timeouts(&mut conn, timeouts).await?;
+    Ok(conn)
+}
+
+/// Poll a replica until the row written on the primary is readable
+async fn wait_for_mysql_replica(
+    mut conn: MySqlConnection,
+    table_name: &str,
+    row: WrittenRow,
+) -> Result<()> {
+    let select_sql = format!("SELECT uuid FROM {table_name} WHERE id = ?");
+    let expected = row.uuid.to_string();
+    loop {
+        let uuid: Option<String> = sqlx::query_scalar(&select_sql)
+            .bind(row.id)
+            .fetch_optional(&mut conn)
+            .await?;
+        if uuid.as_deref() == Some(expected.as_str()) {
+            break;
+        }
+        time::sleep(replica::POLL_INTERVAL).await;
+    }
+    let _ = conn.close().await;
+    Ok(())
 }
 
 async fn mysql_transaction_rollback_test(
+    target: &str,
     conn: &mut MySqlConnection,
     now: DateTime<Utc>,
     table_name: &str,
@@ -427,12 +1172,13 @@ async fn mysql_transaction_rollback_test(
     }
 
     OPERATION_DURATION
-        .with_label_values(&["mysql", "transaction_test"])
+        .with_label_values(&[target, "mysql", "transaction_test"])
         .observe(transaction_timer.elapsed().as_secs_f64());
     Ok(())
 }
 
 async fn mysql_cleanup_old_records(
+    target: &str,
     conn: &mut MySqlConnection,
     now: DateTime<Utc>,
     table_name: &str,
@@ -446,15 +1192,19 @@ async fn mysql_cleanup_old_records(
         .await
     {
         ROWS_AFFECTED
-            .with_label_values(&["mysql", "delete"])
+            .with_label_values(&[target, "mysql", "delete"])
             .inc_by(delete_result.rows_affected());
     }
     OPERATION_DURATION
-        .with_label_values(&["mysql", "cleanup"])
+        .with_label_values(&[target, "mysql", "cleanup"])
         .observe(cleanup_timer.elapsed().as_secs_f64());
 }
 
-async fn update_mysql_table_rows_metric(conn: &mut MySqlConnection, table_name: &str) {
+async fn update_mysql_table_rows_metric(
+    target: &str,
+    conn: &mut MySqlConnection,
+    table_name: &str,
+) {
     let row_count_sql = format!(
         "SELECT CAST(table_rows AS SIGNED) FROM information_schema.TABLES WHERE table_schema = DATABASE() AND table_name = '{table_name}'"
     );
@@ -463,9 +1213,7 @@ async fn update_mysql_table_rows_metric(conn: &mut MySqlConnection, table_name:
         .await
     {
         Ok(Some(Some(row_count))) => {
-            TABLE_ROWS
-                .with_label_values(&["mysql", table_name])
-                .set(row_count);
+            TABLE_ROWS.set_single(target, &[target, "mysql", table_name], row_count);
         }
         Ok(Some(None) | None) => {
             let count_sql = format!("SELECT COUNT(*) FROM {table_name}");
@@ -473,9 +1221,7 @@ async fn update_mysql_table_rows_metric(conn: &mut MySqlConnection, table_name:
                 .fetch_optional(&mut *conn)
                 .await
             {
-                TABLE_ROWS
-                    .with_label_values(&["mysql", table_name])
-                    .set(exact_count);
+                TABLE_ROWS.set_single(target, &[target, "mysql", table_name], exact_count);
             }
         }
         Err(e) => eprintln!("Error querying table_rows for '{table_name}': {e}"),
@@ -501,7 +1247,7 @@ async fn maybe_drop_mysql_table_hourly(
     }
 }
 
-async fn update_mysql_size_metrics(conn: &mut MySqlConnection, table_name: &str) {
+async fn update_mysql_size_metrics(target: &str, conn: &mut MySqlConnection, table_name: &str) {
     let size_sql = format!(
         "SELECT CAST(COALESCE(data_length, 0) + COALESCE(index_length, 0) AS SIGNED) FROM information_schema.TABLES WHERE table_schema = DATABASE() AND table_name = '{table_name}'"
     );
@@ -510,14 +1256,10 @@ async fn update_mysql_size_metrics(conn: &mut MySqlConnection, table_name: &str)
         .await
     {
         Ok(Some(table_bytes)) => {
-            TABLE_SIZE_BYTES
-                .with_label_values(&["mysql", table_name])
-                .set(table_bytes);
+            TABLE_SIZE_BYTES.set_single(target, &[target, "mysql", table_name], table_bytes);
         }
         Ok(None) => {
-            TABLE_SIZE_BYTES
-                .with_label_values(&["mysql", table_name])
-                .set(0);
+            TABLE_SIZE_BYTES.set_single(target, &[target, "mysql", table_name], 0);
         }
         Err(e) => eprintln!("Error querying table size for '{table_name}': {e}"),
     }
@@ -528,18 +1270,78 @@ async fn update_mysql_size_metrics(conn: &mut MySqlConnection, table_name: &str)
     .fetch_optional(&mut *conn)
     .await
     {
-        DATABASE_SIZE_BYTES.with_label_values(&["mysql"]).set(db_size);
+        DATABASE_SIZE_BYTES.with_label_values(&[target, "mysql"]).set(db_size);
     }
 }
 
+/// Collect the `InnoDB` buffer pool, purge, redo log and lock statistics on the slow cadence
+async fn maybe_update_innodb_metrics(target: &str, conn: &mut MySqlConnection) {
+    if !INNODB_CADENCE.due(target, SLOW_COLLECTOR_INTERVAL) {
+        return;
+    }
+
+    let Ok(status) = sqlx::query(r"SHOW GLOBAL STATUS LIKE 'Innodb\_%'")
+        .fetch_all(&mut *conn)
+        .await
+    else {
+        return;
+    };
+    let variables = sqlx::query(&format!(
+        "SHOW GLOBAL VARIABLES WHERE Variable_name IN ('{}')",
+        innodb::REDO_LOG_VARIABLES.join("', '")
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    .unwrap_or_default();
+    let mut values: HashMap<String, String> = status
+        .iter()
+        .chain(&variables)
+        .filter_map(|row| {
+            Some((
+                mysql_column_text(row, "Variable_name")?.to_ascii_lowercase(),
+                mysql_column_text(row, "Value")?,
+            ))
+        })
+        .collect();
+
+    if let Ok(rows) = sqlx::query(&format!(
+        "SELECT NAME, CAST(COUNT AS SIGNED) AS COUNT FROM information_schema.INNODB_METRICS \
+         WHERE NAME IN ('{}')",
+        innodb::INNODB_METRICS.join("', '")
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    {
+        values.extend(rows.iter().filter_map(|row| {
+            Some((
+                mysql_column_text(row, "NAME")?.to_ascii_lowercase(),
+                mysql_column_text(row, "COUNT")?,
+            ))
+        }));
+    }
+
+    // needs the PROCESS privilege
+    let engine_status = sqlx::query("SHOW ENGINE INNODB STATUS")
+        .fetch_optional(&mut *conn)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|row| mysql_column_text(&row, "Status"));
+
+    innodb::record(
+        target,
+        "mysql",
+        &InnodbStatus::parse(&values, engine_status.as_deref()),
+    );
+}
+
 async fn maybe_extract_mysql_tls(
-    dsn: &DSN,
-    tls: &TlsConfig,
+    target: &Target,
     conn: &mut MySqlConnection,
     cert_cache: &CertCache,
 ) -> Option<TlsMetadata> {
-    if tls.mode.is_enabled() {
-        extract_tls_metadata(dsn, tls, conn, cert_cache).await.ok()
+    if target.tls.mode.is_enabled() {
+        extract_tls_metadata(target, conn, cert_cache).await.ok()
     } else {
         None
     }
@@ -547,11 +1349,11 @@ async fn maybe_extract_mysql_tls(
 
 /// Extract TLS metadata from `MySQL` connection
 async fn extract_tls_metadata(
-    dsn: &DSN,
-    tls: &TlsConfig,
+    target: &Target,
     conn: &mut sqlx::MySqlConnection,
     cert_cache: &CertCache,
 ) -> Result<TlsMetadata> {
+    let (dsn, tls) = (hosts::selected(target), &target.tls);
     let mut cert_subject: Option<String> = None;
     let mut cert_issuer: Option<String> = None;
     let mut cert_expiry_days: Option<i64> = None;
@@ -583,7 +1385,7 @@ async fn extract_tls_metadata(
                     "unknown"
                 };
                 TLS_CERT_PROBE_ERRORS
-                    .with_label_values(&["mysql", error_type])
+                    .with_label_values(&[target.name.as_str(), "mysql", error_type])
                     .inc();
             }
         }
@@ -602,15 +1404,11 @@ async fn extract_tls_metadata(
         let value: String = row.try_get(1)?;
 
         match variable_name.as_str() {
-            "Ssl_version" => {
-                if !value.is_empty() {
-                    tls_version = Some(value);
-                }
+            "Ssl_version" if !value.is_empty() => {
+                tls_version = Some(value);
             }
-            "Ssl_cipher" => {
-                if !value.is_empty() {
-                    tls_cipher = Some(value);
-                }
+            "Ssl_cipher" if !value.is_empty() => {
+                tls_cipher = Some(value);
             }
             "Ssl_server_not_after" => {
                 if cert_expiry_days.is_none()
@@ -666,4 +1464,66 @@ mod tests {
         assert_eq!(parse_mysql_ssl_expiry("0000-00-00 00:00:00"), None);
         assert_eq!(parse_mysql_ssl_expiry("not a date"), None);
     }
+
+    fn channel(columns: &[(&str, &str)]) -> ReplicaChannel {
+        ReplicaChannel::parse(|name| {
+            columns
+                .iter()
+                .find(|(column, _)| *column == name)
+                .map(|(_, value)| (*value).to_string())
+        })
+    }
+
+    #[test]
+    fn test_replica_channel_mysql() {
+        let uuid = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+        let retrieved = format!("{uuid}:1-120");
+        let executed = format!("{uuid}:1-100");
+        let status = channel(&[
+            ("Channel_Name", "orders"),
+            ("Replica_IO_Running", "Yes"),
+            ("Replica_SQL_Running", "No"),
+            ("Last_IO_Errno", "0"),
+            ("Last_SQL_Errno", "1062"),
+            ("Relay_Log_Space", "4096"),
+            ("Retrieved_Gtid_Set", &retrieved),
+            ("Executed_Gtid_Set", &executed),
+        ]);
+        assert_eq!(
+            status,
+            ReplicaChannel {
+                channel: "orders".to_string(),
+                io_running: true,
+                sql_running: false,
+                last_io_errno: 0,
+                last_sql_errno: 1062,
+                relay_log_space: Some(4096),
+                // NULL while the SQL thread is stopped
+                lag_seconds: None,
+                gtid_gap: Some(20),
+            }
+        );
+    }
+
+    #[test]
+    fn test_replica_channel_mariadb() {
+        let status = channel(&[
+            ("Connection_name", ""),
+            ("Slave_IO_Running", "Connecting"),
+            ("Slave_SQL_Running", "Yes"),
+            ("Last_IO_Errno", "2003"),
+            ("Seconds_Behind_Master", "3"),
+            ("Gtid_IO_Pos", "0-1-50"),
+            ("Gtid_Slave_Pos", "0-1-48"),
+        ]);
+        assert_eq!(status.channel, "");
+        assert!(!status.io_running);
+        assert!(status.sql_running);
+        assert_eq!(status.last_io_errno, 2003);
+        assert_eq!(status.lag_seconds, Some(3));
+        assert_eq!(status.gtid_gap, Some(2));
+
+        // without GTID replication there is no gap to report
+        assert_eq!(channel(&[("Gtid_IO_Pos", "")]).gtid_gap, None);
+    }
 }
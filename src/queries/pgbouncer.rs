@@ -0,0 +1,334 @@
+//! `PgBouncer` admin console
+//!
+//! `pgbouncer://` targets connect to the special `pgbouncer` database of the
+//! pooler as an admin or stats user. The console only answers `SHOW`
+//! commands over the simple query protocol; the pools (`SHOW POOLS`) and the
+//! totals of each database (`SHOW STATS`) are exported. The read/write test
+//! through the pooler is a regular `postgres://` target on its port.
+
+use super::{
+    CheckError, DeadlineExceeded, ErrorKind, HealthCheckResult,
+    hosts::{self, HostRole},
+    postgres::{postgres_connect_options, server_error_kind},
+    resolve_host,
+};
+use crate::{
+    metrics::{
+        OPERATION_DURATION, PGBOUNCER_CLIENTS, PGBOUNCER_MAX_WAIT_SECONDS, PGBOUNCER_QUERIES,
+        PGBOUNCER_SERVERS, PGBOUNCER_TRANSACTIONS, PGBOUNCER_WAIT_SECONDS,
+    },
+    target::Target,
+    tls::ensure_crypto_provider,
+};
+use anyhow::{Context, Result};
+use dsn::DSN;
+use sqlx::{ConnectOptions, Connection, Executor, PgConnection, Row, postgres::PgRow};
+use std::{collections::HashSet, time::Instant};
+use tokio::time;
+
+/// Port `PgBouncer` listens on when the DSN does not give one
+pub(crate) const DEFAULT_PORT: u16 = 6432;
+
+/// Database of the admin console, also listed as a pool by `SHOW POOLS`
+const ADMIN_DATABASE: &str = "pgbouncer";
+
+/// States of `dbpulse_pgbouncer_clients`, in the order of [`Pool::clients`]
+const CLIENT_STATES: [&str; 2] = ["active", "waiting"];
+
+/// States of `dbpulse_pgbouncer_servers`, in the order of [`Pool::servers`]
+const SERVER_STATES: [&str; 5] = ["active", "idle", "used", "tested", "login"];
+
+/// A row of `SHOW POOLS`, one per database and user
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Pool {
+    pub(crate) database: String,
+    pub(crate) user: String,
+    /// `cl_active`, `cl_waiting`
+    pub(crate) clients: [i64; 2],
+    /// `sv_active`, `sv_idle`, `sv_used`, `sv_tested`, `sv_login`
+    pub(crate) servers: [i64; 5],
+    /// `maxwait` and `maxwait_us`
+    pub(crate) max_wait_seconds: f64,
+}
+
+/// A row of `SHOW STATS`, totals of a database since `PgBouncer` started
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DatabaseStats {
+    pub(crate) database: String,
+    pub(crate) transactions: i64,
+    pub(crate) queries: i64,
+    pub(crate) wait_seconds: f64,
+}
+
+impl Pool {
+    /// Parse a row from its columns by name, `None` for the admin console itself
+    ///
+    /// Columns missing in older releases count as zero.
+    pub(crate) fn parse(column: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let database = column("database")?;
+        if database == ADMIN_DATABASE {
+            return None;
+        }
+        let number = |name: &str| integer(column(name));
+        Some(Self {
+            database,
+            user: column("user").unwrap_or_default(),
+            clients: ["cl_active", "cl_waiting"].map(number),
+            servers: ["sv_active", "sv_idle", "sv_used", "sv_tested", "sv_login"].map(number),
+            max_wait_seconds: seconds(number("maxwait"), number("maxwait_us")),
+        })
+    }
+}
+
+impl DatabaseStats {
+    /// Parse a row from its columns by name, `None` for the admin console itself
+    pub(crate) fn parse(column: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let database = column("database")?;
+        if database == ADMIN_DATABASE {
+            return None;
+        }
+        Some(Self {
+            database,
+            transactions: integer(column("total_xact_count")),
+            queries: integer(column("total_query_count")),
+            // microseconds
+            wait_seconds: seconds(0, integer(column("total_wait_time"))),
+        })
+    }
+}
+
+fn integer(value: Option<String>) -> i64 {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn seconds(seconds: i64, microseconds: i64) -> f64 {
+    seconds as f64 + microseconds as f64 / 1_000_000.0
+}
+
+/// Check the admin console of the target and export its pools
+///
+/// # Errors
+///
+/// Returns an error if the console cannot be reached or a `SHOW` command fails
+pub async fn test_admin(target: &Target) -> Result<HealthCheckResult> {
+    let result = async {
+        let mut conn = hosts::connect(target, "pgbouncer", |dsn| open_console(target, dsn)).await?;
+        let result = collect(&target.name, &mut conn).await;
+        let _ = conn.close().await;
+        result
+    }
+    .await;
+    result.map_err(|error| CheckError::classify(error, server_error_kind, ErrorKind::Query))
+}
+
+/// Connect to the console of one of the listed hosts
+///
+/// `extra_float_digits` is not a startup parameter `PgBouncer` accepts.
+async fn open_console(target: &Target, dsn: &DSN) -> Result<(PgConnection, HostRole)> {
+    ensure_crypto_provider();
+    let options = postgres_connect_options(dsn, &target.tls)
+        .port(dsn.port.unwrap_or(DEFAULT_PORT))
+        .extra_float_digits(None);
+    let connect = async {
+        resolve_host(&target.name, "pgbouncer", dsn, DEFAULT_PORT).await?;
+        let timer = Instant::now();
+        let conn = options.connect().await?;
+        OPERATION_DURATION
+            .with_label_values(&[target.name.as_str(), "pgbouncer", "connect"])
+            .observe(timer.elapsed().as_secs_f64());
+        anyhow::Ok(conn)
+    };
+    let conn = time::timeout(target.timeouts.connect, connect)
+        .await
+        .map_err(|_| DeadlineExceeded {
+            operation: "connect",
+            limit: target.timeouts.connect,
+        })??;
+    Ok((conn, HostRole::default()))
+}
+
+async fn collect(target: &str, conn: &mut PgConnection) -> Result<HealthCheckResult> {
+    // releases before 1.12 answer SHOW VERSION with a notice
+    let version = (&mut *conn)
+        .fetch_optional(sqlx::raw_sql("SHOW VERSION"))
+        .await
+        .context("Failed to fetch PgBouncer version")?
+        .and_then(|row| column(&row, "version"))
+        .unwrap_or_else(|| "PgBouncer".to_string());
+
+    let pools = (&mut *conn)
+        .fetch_all(sqlx::raw_sql("SHOW POOLS"))
+        .await
+        .context("Failed to fetch PgBouncer pools")?
+        .iter()
+        .filter_map(|row| Pool::parse(|name| column(row, name)))
+        .collect::<Vec<_>>();
+    let stats = (&mut *conn)
+        .fetch_all(sqlx::raw_sql("SHOW STATS"))
+        .await
+        .context("Failed to fetch PgBouncer stats")?
+        .iter()
+        .filter_map(|row| DatabaseStats::parse(|name| column(row, name)))
+        .collect::<Vec<_>>();
+    record(target, "pgbouncer", &pools, &stats);
+
+    Ok(HealthCheckResult {
+        version,
+        flavor: "pgbouncer",
+        db_host: None,
+        uptime_seconds: None,
+        replication_lag_seconds: None,
+        tls_metadata: None,
+    })
+}
+
+/// Column of a console row as text
+///
+/// The simple query protocol returns every value as text, whatever type
+/// the console declares for the column.
+fn column(row: &PgRow, name: &str) -> Option<String> {
+    row.try_get_unchecked::<Option<String>, _>(name)
+        .ok()
+        .flatten()
+}
+
+/// Export the pools and database totals, removing the ones no longer listed
+pub(crate) fn record(target: &str, database: &str, pools: &[Pool], stats: &[DatabaseStats]) {
+    let (mut clients, mut servers, mut series) = (HashSet::new(), HashSet::new(), HashSet::new());
+    for pool in pools {
+        let labels = [target, database, pool.database.as_str(), pool.user.as_str()];
+        for (state, count) in CLIENT_STATES.into_iter().zip(pool.clients) {
+            let labels = [labels.as_slice(), &[state]].concat();
+            PGBOUNCER_CLIENTS.vec.with_label_values(&labels).set(count);
+            clients.insert(labels.iter().map(ToString::to_string).collect());
+        }
+        for (state, count) in SERVER_STATES.into_iter().zip(pool.servers) {
+            let labels = [labels.as_slice(), &[state]].concat();
+            PGBOUNCER_SERVERS.vec.with_label_values(&labels).set(count);
+            servers.insert(labels.iter().map(ToString::to_string).collect());
+        }
+        PGBOUNCER_MAX_WAIT_SECONDS
+            .vec
+            .with_label_values(&labels)
+            .set(pool.max_wait_seconds);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    PGBOUNCER_CLIENTS.replace(target, clients);
+    PGBOUNCER_SERVERS.replace(target, servers);
+    PGBOUNCER_MAX_WAIT_SECONDS.replace(target, series);
+
+    let mut series = HashSet::new();
+    for totals in stats {
+        let labels = [target, database, totals.database.as_str()];
+        PGBOUNCER_TRANSACTIONS
+            .vec
+            .with_label_values(&labels)
+            .set(totals.transactions);
+        PGBOUNCER_QUERIES
+            .vec
+            .with_label_values(&labels)
+            .set(totals.queries);
+        PGBOUNCER_WAIT_SECONDS
+            .vec
+            .with_label_values(&labels)
+            .set(totals.wait_seconds);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    PGBOUNCER_TRANSACTIONS.replace(target, series.clone());
+    PGBOUNCER_QUERIES.replace(target, series.clone());
+    PGBOUNCER_WAIT_SECONDS.replace(target, series);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+    use std::collections::HashMap;
+
+    fn columns(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let row = columns(&[
+            ("database", "app"),
+            ("user", "app_rw"),
+            ("cl_active", "12"),
+            ("cl_waiting", "3"),
+            ("sv_active", "10"),
+            ("sv_idle", "2"),
+            ("sv_login", "1"),
+            ("maxwait", "1"),
+            ("maxwait_us", "500000"),
+            ("pool_mode", "transaction"),
+        ]);
+        let pool = Pool::parse(|name| row.get(name).cloned()).unwrap();
+        assert_eq!(pool.clients, [12, 3]);
+        // sv_used and sv_tested are missing
+        assert_eq!(pool.servers, [10, 2, 0, 0, 1]);
+        assert!((pool.max_wait_seconds - 1.5).abs() < f64::EPSILON);
+
+        let console = columns(&[("database", "pgbouncer"), ("user", "pgbouncer")]);
+        assert_eq!(Pool::parse(|name| console.get(name).cloned()), None);
+
+        let row = columns(&[
+            ("database", "app"),
+            ("total_xact_count", "1200"),
+            ("total_query_count", "4800"),
+            ("total_wait_time", "2500000"),
+        ]);
+        let stats = DatabaseStats::parse(|name| row.get(name).cloned()).unwrap();
+        assert_eq!((stats.transactions, stats.queries), (1200, 4800));
+        assert!((stats.wait_seconds - 2.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_record() {
+        let pool = |user: &str| Pool {
+            database: "app".to_string(),
+            user: user.to_string(),
+            clients: [4, 1],
+            servers: [2, 3, 0, 0, 0],
+            max_wait_seconds: 0.25,
+        };
+        let stats = DatabaseStats {
+            database: "app".to_string(),
+            transactions: 42,
+            queries: 84,
+            wait_seconds: 1.0,
+        };
+        record(
+            "pgb",
+            "pgbouncer",
+            &[pool("app_rw"), pool("app_ro")],
+            &[stats],
+        );
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_pgbouncer_clients{database="pgbouncer",pool="app",state="waiting",target="pgb",user="app_ro"} 1"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_pgbouncer_servers{database="pgbouncer",pool="app",state="idle",target="pgb",user="app_rw"} 3"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_pgbouncer_transactions{database="pgbouncer",pool="app",target="pgb"} 42"#
+        ));
+
+        // the app_ro pool is gone
+        record("pgb", "pgbouncer", &[pool("app_rw")], &[]);
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"target="pgb",user="app_ro""#));
+        assert!(!output.contains(
+            r#"dbpulse_pgbouncer_transactions{database="pgbouncer",pool="app",target="pgb"}"#
+        ));
+    }
+}
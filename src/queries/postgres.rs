@@ -1,10 +1,25 @@
-use super::HealthCheckResult;
+use super::{
+    CheckError, DeadlineExceeded, ErrorKind, HealthCheckResult, addresses,
+    cadence::{SLOW_COLLECTOR_INTERVAL, VACUUM_CADENCE},
+    engine::{self, ClusterNodes, Engine, Probe},
+    hosts::{self, HostRole},
+    locks::{self, Blocker, LockReport},
+    replica,
+    replica::WrittenRow,
+    resolve_host,
+    session::{Session, Slot},
+    vacuum::{self, DatabaseAge, VacuumReport},
+};
 use crate::{
+    custom::{ValueType, integer_value},
     metrics::{
-        BLOCKING_QUERIES, CONNECTION_DURATION, DATABASE_SIZE_BYTES, OPERATION_DURATION,
-        REPLICATION_LAG, ROWS_AFFECTED, TABLE_ROWS, TABLE_SIZE_BYTES, TLS_CERT_PROBE_ERRORS,
-        TLS_HANDSHAKE_DURATION,
+        CONNECTION_STATES, CONNECTIONS_BY_STATE, CONNECTIONS_CURRENT, CONNECTIONS_MAX,
+        DATABASE_SIZE_BYTES, LONG_IDLE_IN_TRANSACTION, OPERATION_DURATION, REPLICATION_LAG,
+        REPLICATION_SLOT_ACTIVE, REPLICATION_SLOT_RETAINED_BYTES, ROWS_AFFECTED, STANDBY_LAG_BYTES,
+        STANDBY_LAG_SECONDS, TABLE_ROWS, TABLE_SIZE_BYTES, TLS_CERT_PROBE_ERRORS,
+        TLS_HANDSHAKE_DURATION, remove_replication_series,
     },
+    target::{Replica, SessionAttrs, Target, Timeouts},
     tls::{
         TlsConfig, TlsMetadata, TlsMode, TlsProbeProtocol,
         cache::{CertCache, get_cert_metadata_cached},
@@ -17,24 +32,28 @@ use dsn::DSN;
 use rand::Rng;
 use sqlx::{
     ConnectOptions, Connection, Row,
+    error::DatabaseError,
     postgres::{PgConnectOptions, PgDatabaseError, PgSslMode},
 };
-use std::time::Instant;
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use tokio::time;
 use uuid::Uuid;
 
-/// Test read/write operations on the default table
+/// Test read/write operations on the table configured for the target
 ///
 /// # Errors
 ///
 /// Returns an error if database connection or operations fail
 pub async fn test_rw(
-    dsn: &DSN,
+    target: &Target,
     now: DateTime<Utc>,
-    range: u32,
-    tls: &TlsConfig,
     cert_cache: &CertCache,
 ) -> Result<HealthCheckResult> {
-    test_rw_with_table(dsn, now, range, tls, cert_cache, "dbpulse_rw").await
+    test_rw_with_table(target, now, cert_cache, &target.table).await
 }
 
 /// Test read/write operations on a specified table
@@ -43,79 +62,293 @@ pub async fn test_rw(
 ///
 /// Returns an error if database connection or operations fail
 pub async fn test_rw_with_table(
-    dsn: &DSN,
+    target: &Target,
+    now: DateTime<Utc>,
+    cert_cache: &CertCache,
+    table_name: &str,
+) -> Result<HealthCheckResult> {
+    let mut session = Session::default();
+    let result = check_rw(target, now, cert_cache, table_name, &mut session.postgres).await;
+    session.close().await;
+    result.map_err(classify)
+}
+
+/// Test read/write operations reusing the connection kept in `session`
+///
+/// In persistent connection mode the connection stays open for the next
+/// check; it is replaced when it no longer answers a ping or a check fails.
+///
+/// # Errors
+///
+/// Returns an error if database connection or operations fail
+pub async fn test_rw_in_session(
+    target: &Target,
+    now: DateTime<Utc>,
+    cert_cache: &CertCache,
+    session: &mut Session,
+) -> Result<HealthCheckResult> {
+    check_rw(
+        target,
+        now,
+        cert_cache,
+        &target.table,
+        &mut session.postgres,
+    )
+    .await
+    .map_err(classify)
+}
+
+fn classify(error: anyhow::Error) -> anyhow::Error {
+    CheckError::classify(error, server_error_kind, ErrorKind::Query)
+}
+
+async fn check_rw(
+    target: &Target,
     now: DateTime<Utc>,
-    range: u32,
-    tls: &TlsConfig,
     cert_cache: &CertCache,
     table_name: &str,
+    slot: &mut Slot<sqlx::PgConnection>,
 ) -> Result<HealthCheckResult> {
+    let reused = slot
+        .reuse_with_role(target, "postgres", probe_postgres_role)
+        .await;
+    let (mut conn, opened) = if let Some(live) = reused {
+        live
+    } else {
+        let opened = Instant::now();
+        let conn = open_postgres(target).await?;
+        slot.opened(target, "postgres");
+        (conn, opened)
+    };
+    let result = run_postgres_checks(target, &mut conn, now, cert_cache, table_name).await;
+    slot.release(target, "postgres", conn, opened, result.is_ok())
+        .await;
+    result
+}
+
+/// Run the read-only part of the check against every address of the target
+pub(crate) async fn probe_addresses(target: &Target) {
+    let port = Engine::from_driver(&target.dsn.driver).default_port();
+    addresses::probe(target, "postgres", port, |address| {
+        check_postgres_address(target, address, &target.table)
+    })
+    .await;
+}
+
+/// Connect to the host selected by `target_session_attrs` and apply the session timeouts
+async fn open_postgres(target: &Target) -> Result<sqlx::PgConnection> {
+    hosts::connect(target, "postgres", |dsn| open_postgres_host(target, dsn)).await
+}
+
+/// Connect to one of the listed hosts, probing its role unless any host will do
+async fn open_postgres_host(target: &Target, dsn: &DSN) -> Result<(sqlx::PgConnection, HostRole)> {
+    let tls = &target.tls;
     ensure_crypto_provider();
     let options = postgres_connect_options(dsn, tls);
-    let conn_start = Instant::now();
-    let mut conn = connect_postgres(&options, dsn, tls).await?;
-    set_postgres_session_timeouts(&mut conn).await?;
+    let mut conn = time::timeout(
+        target.timeouts.connect,
+        connect_postgres(target.name.as_str(), &options, dsn, tls),
+    )
+    .await
+    .map_err(|_| DeadlineExceeded {
+        operation: "connect",
+        limit: target.timeouts.connect,
+    })??;
+    set_postgres_session_timeouts(&mut conn, target.timeouts).await?;
+    if target.session_attrs == SessionAttrs::Any {
+        return Ok((conn, HostRole::default()));
+    }
+    let role = probe_postgres_role(&mut conn).await?;
+    Ok((conn, role))
+}
 
-    let health_info = fetch_postgres_health_info(&mut conn).await?;
-    if postgres_is_in_recovery(&mut conn).await? {
-        maybe_record_postgres_replication_lag(&mut conn).await;
+/// Role of the host a connection is open to
+async fn probe_postgres_role(conn: &mut sqlx::PgConnection) -> Result<HostRole> {
+    let engine = Engine::detect(&fetch_postgres_engine_version(conn).await?);
+    Ok(HostRole {
+        standby: engine.supports(Probe::Standbys) && postgres_is_in_recovery(conn).await?,
+        read_only: postgres_transaction_is_read_only(conn).await?,
+    })
+}
+
+/// Connect to a resolved address with the credentials of the target and read
+/// the table of the read/write test, the part of the check any backend must serve
+async fn check_postgres_address(
+    target: &Target,
+    address: SocketAddr,
+    table_name: &str,
+) -> Result<()> {
+    ensure_crypto_provider();
+    let options = postgres_connect_options(&target.dsn, &addresses::address_tls(&target.tls))
+        .host(&address.ip().to_string())
+        .port(address.port());
+    let mut conn = time::timeout(target.timeouts.connect, options.connect())
+        .await
+        .map_err(|_| DeadlineExceeded {
+            operation: "connect",
+            limit: target.timeouts.connect,
+        })?
+        .map_err(|e| CheckError::classify(e.into(), server_error_kind, ErrorKind::Connection))?;
+    let read = async {
+        set_postgres_session_timeouts(&mut conn, target.timeouts).await?;
+        sqlx::query(&format!("SELECT t1 FROM {table_name} LIMIT 1"))
+            .fetch_optional(&mut conn)
+            .await
+            .context("Failed to read the table")?;
+        anyhow::Ok(())
+    };
+    let result = read.await.map_err(classify);
+    let _ = conn.close().await;
+    result
+}
+
+async fn run_postgres_checks(
+    target: &Target,
+    conn: &mut sqlx::PgConnection,
+    now: DateTime<Utc>,
+    cert_cache: &CertCache,
+    table_name: &str,
+) -> Result<HealthCheckResult> {
+    let (name, range) = (target.name.as_str(), target.range);
+
+    let health_info = fetch_postgres_health_info(conn).await?;
+    let engine = health_info.engine;
+    update_cluster_nodes(name, conn, engine).await;
+    if engine.supports(Probe::Standbys) && postgres_is_in_recovery(conn).await? {
+        // standbys and slots are reported by the primary
+        remove_replication_series(name);
+        let lag = maybe_record_postgres_replication_lag(name, conn).await;
         return postgres_read_only_result(
-            dsn,
-            tls,
-            &mut conn,
+            target,
+            conn,
             cert_cache,
             health_info,
+            lag,
             "Database is in recovery mode",
         )
         .await;
     }
-    if postgres_transaction_is_read_only(&mut conn).await? {
+    if postgres_transaction_is_read_only(conn).await? {
         return postgres_read_only_result(
-            dsn,
-            tls,
-            &mut conn,
+            target,
+            conn,
             cert_cache,
             health_info,
+            None,
             "Transaction read-only mode enabled",
         )
         .await;
     }
 
-    monitor_postgres_blocking_queries(&mut conn).await;
-    ensure_postgres_table(&mut conn, table_name).await?;
-    let id = postgres_insert_and_verify(&mut conn, now, range, table_name).await?;
-    postgres_transaction_rollback_test(&mut conn, now, table_name).await?;
-    postgres_cleanup_old_records(&mut conn, table_name).await;
-    update_postgres_table_rows_metric(&mut conn, table_name).await;
-    maybe_drop_postgres_table_hourly(&mut conn, now, id, table_name).await;
-    update_postgres_size_metrics(&mut conn, table_name).await;
+    if engine.supports(Probe::LockWaits) {
+        monitor_postgres_locks(name, conn).await;
+    }
+    if engine.supports(Probe::Sessions) {
+        monitor_postgres_connections(name, conn, target.timeouts.idle_in_transaction).await;
+    }
+    if engine.supports(Probe::Standbys) {
+        update_postgres_replication_metrics(name, conn).await;
+    }
+    ensure_postgres_table(name, conn, table_name).await?;
+    let replicas = replica::connect(target, |replica| {
+        connect_postgres_replica(replica, target.timeouts)
+    })
+    .await;
+    let row = match postgres_insert_and_verify(name, conn, now, range, table_name).await {
+        Ok(row) => row,
+        Err(error) => return Err(explain_postgres_lock_timeout(conn, table_name, error).await),
+    };
+    replica::measure(target, "postgres", row, replicas, |conn| {
+        wait_for_postgres_replica(conn, table_name, row)
+    })
+    .await;
+    if let Err(error) = retry_postgres_rollback_test(name, conn, now, table_name, engine).await {
+        let error = CheckError::classify(error, server_error_kind, ErrorKind::Transaction);
+        return Err(explain_postgres_lock_timeout(conn, table_name, error).await);
+    }
+    postgres_cleanup_old_records(name, conn, table_name).await;
+    maybe_drop_postgres_table_hourly(conn, now, row.id, table_name).await;
+    if engine.supports(Probe::Sizes) {
+        update_postgres_table_rows_metric(name, conn, table_name).await;
+        update_postgres_size_metrics(name, conn, table_name).await;
+    }
+    if engine.supports(Probe::Vacuum) {
+        maybe_update_postgres_vacuum_metrics(name, conn).await;
+    }
 
-    let tls_metadata = maybe_extract_postgres_tls(dsn, tls, &mut conn, cert_cache).await;
-    let _ = conn.close().await;
-    CONNECTION_DURATION.observe(conn_start.elapsed().as_secs_f64());
+    let tls_metadata = maybe_extract_postgres_tls(target, conn, cert_cache, engine).await;
 
     Ok(HealthCheckResult {
         version: health_info.version.context("Expected database version")?,
+        flavor: engine.as_str(),
         db_host: health_info.db_host,
         uptime_seconds: health_info.uptime_seconds,
+        replication_lag_seconds: None,
         tls_metadata,
     })
 }
 
+/// Run the query of a custom check and return its scalar result
+///
+/// Uses the same connection options, TLS settings and timeouts as the
+/// read/write test.
+///
+/// # Errors
+///
+/// Returns an error if the connection or query fails, the query returns no
+/// row or `NULL`, or the value does not have the expected type
+pub(crate) async fn custom_check_value(
+    target: &Target,
+    sql: &str,
+    value_type: ValueType,
+) -> Result<f64> {
+    let mut conn = open_postgres(target).await?;
+    let value = fetch_postgres_scalar(&mut conn, sql, value_type).await;
+    let _ = conn.close().await;
+    value.map_err(classify)
+}
+
+async fn fetch_postgres_scalar(
+    conn: &mut sqlx::PgConnection,
+    sql: &str,
+    value_type: ValueType,
+) -> Result<f64> {
+    let value = match value_type {
+        ValueType::Integer => sqlx::query_scalar::<_, Option<i64>>(sql)
+            .fetch_one(&mut *conn)
+            .await?
+            .map(integer_value),
+        ValueType::Float => {
+            sqlx::query_scalar::<_, Option<f64>>(sql)
+                .fetch_one(&mut *conn)
+                .await?
+        }
+        ValueType::Boolean => sqlx::query_scalar::<_, Option<bool>>(sql)
+            .fetch_one(&mut *conn)
+            .await?
+            .map(|value| if value { 1.0 } else { 0.0 }),
+    };
+    value.context("Query returned NULL")
+}
+
 struct PostgresHealthInfo {
+    engine: Engine,
     version: Option<String>,
     db_host: Option<String>,
     uptime_seconds: Option<i64>,
 }
 
-fn postgres_connect_options(dsn: &DSN, tls: &TlsConfig) -> PgConnectOptions {
+pub(crate) fn postgres_connect_options(dsn: &DSN, tls: &TlsConfig) -> PgConnectOptions {
     let mut options = PgConnectOptions::new()
         .username(dsn.username.clone().unwrap_or_default().as_ref())
         .password(dsn.password.clone().unwrap_or_default().as_str())
         .database(dsn.database.clone().unwrap_or_default().as_ref());
 
     if let Some(host) = &dsn.host {
-        options = options.host(host.as_str()).port(dsn.port.unwrap_or(5432));
+        let port = dsn
+            .port
+            .unwrap_or(Engine::from_driver(&dsn.driver).default_port());
+        options = options.host(host.as_str()).port(port);
     } else if let Some(socket) = &dsn.socket {
         options = options.socket(socket.as_str());
     }
@@ -146,27 +379,30 @@ fn postgres_connect_options(dsn: &DSN, tls: &TlsConfig) -> PgConnectOptions {
     options
 }
 
-fn record_postgres_connect_metrics(tls: &TlsConfig, connect_timer: Instant) {
+fn record_postgres_connect_metrics(target: &str, tls: &TlsConfig, connect_timer: Instant) {
     let connect_duration = connect_timer.elapsed().as_secs_f64();
     OPERATION_DURATION
-        .with_label_values(&["postgres", "connect"])
+        .with_label_values(&[target, "postgres", "connect"])
         .observe(connect_duration);
     if tls.mode.is_enabled() {
         TLS_HANDSHAKE_DURATION
-            .with_label_values(&["postgres"])
+            .with_label_values(&[target, "postgres"])
             .observe(connect_duration);
     }
 }
 
 async fn connect_postgres(
+    target: &str,
     options: &PgConnectOptions,
     dsn: &DSN,
     tls: &TlsConfig,
 ) -> Result<sqlx::PgConnection> {
+    let port = Engine::from_driver(&dsn.driver).default_port();
+    resolve_host(target, "postgres", dsn, port).await?;
     let connect_timer = Instant::now();
     match options.connect().await {
         Ok(conn) => {
-            record_postgres_connect_metrics(tls, connect_timer);
+            record_postgres_connect_metrics(target, tls, connect_timer);
             Ok(conn)
         }
         Err(err) => {
@@ -179,10 +415,10 @@ async fn connect_postgres(
                 {
                     create_postgres_database(options, dsn).await?;
                     let conn = options.connect().await?;
-                    record_postgres_connect_metrics(tls, connect_timer);
+                    record_postgres_connect_metrics(target, tls, connect_timer);
                     Ok(conn)
                 } else {
-                    Err(db_err.into())
+                    Err(sqlx::Error::Database(db_err).into())
                 }
             } else {
                 Err(err.into())
@@ -191,6 +427,28 @@ async fn connect_postgres(
     }
 }
 
+/// Category of a `PostgreSQL` server error, by SQLSTATE
+pub(crate) fn server_error_kind(error: &dyn DatabaseError) -> Option<ErrorKind> {
+    let code = error.try_downcast_ref::<PgDatabaseError>()?.code();
+    Some(match code {
+        // invalid_password, invalid_authorization_specification
+        "28P01" | "28000" => ErrorKind::Authentication,
+        "53300" => ErrorKind::TooManyConnections,
+        // admin_shutdown, crash_shutdown
+        "57P01" | "57P02" => ErrorKind::AdminShutdown,
+        "53100" => ErrorKind::DiskFull,
+        "40P01" => ErrorKind::Deadlock,
+        // query_canceled (statement_timeout), lock_not_available (lock_timeout)
+        "57014" | "55P03" => ErrorKind::Timeout,
+        "25006" => ErrorKind::ReadOnly,
+        // connection_exception class, cannot_connect_now
+        _ if code.starts_with("08") || code == "57P03" => ErrorKind::Connection,
+        // transaction_rollback and invalid_transaction_state classes
+        _ if code.starts_with("40") || code.starts_with("25") => ErrorKind::Transaction,
+        _ => return None,
+    })
+}
+
 async fn create_postgres_database(options: &PgConnectOptions, dsn: &DSN) -> Result<()> {
     let tmp_options = options.clone().database("postgres");
     let mut tmp_conn = tmp_options.connect().await?;
@@ -203,23 +461,46 @@ async fn create_postgres_database(options: &PgConnectOptions, dsn: &DSN) -> Resu
     Ok(())
 }
 
-async fn set_postgres_session_timeouts(conn: &mut sqlx::PgConnection) -> Result<()> {
-    sqlx::query("SET statement_timeout = '5s'")
-        .execute(&mut *conn)
-        .await
-        .context("Failed to set statement timeout")?;
-    sqlx::query("SET lock_timeout = '2s'")
+async fn set_postgres_session_timeouts(
+    conn: &mut sqlx::PgConnection,
+    timeouts: Timeouts,
+) -> Result<()> {
+    // Integer values are interpreted as milliseconds
+    sqlx::query(&format!(
+        "SET statement_timeout = {}",
+        timeouts.statement.as_millis()
+    ))
+    .execute(&mut *conn)
+    .await
+    .context("Failed to set statement timeout")?;
+    sqlx::query(&format!("SET lock_timeout = {}", timeouts.lock.as_millis()))
         .execute(&mut *conn)
         .await
         .context("Failed to set lock timeout")?;
     Ok(())
 }
 
-async fn fetch_postgres_health_info(conn: &mut sqlx::PgConnection) -> Result<PostgresHealthInfo> {
-    let version: Option<String> = sqlx::query_scalar("SHOW server_version")
-        .fetch_optional(&mut *conn)
+/// Text of `version()`, which names the engine
+async fn fetch_postgres_engine_version(conn: &mut sqlx::PgConnection) -> Result<String> {
+    sqlx::query_scalar("SELECT version()")
+        .fetch_one(&mut *conn)
         .await
-        .context("Failed to fetch database version")?;
+        .context("Failed to fetch database version")
+}
+
+async fn fetch_postgres_health_info(conn: &mut sqlx::PgConnection) -> Result<PostgresHealthInfo> {
+    let engine_version = fetch_postgres_engine_version(conn).await?;
+    let engine = Engine::detect(&engine_version);
+    if engine == Engine::Cockroach {
+        return Ok(fetch_cockroach_health_info(conn, &engine_version).await);
+    }
+    let version: Option<String> = match engine.version(&engine_version) {
+        Some(version) => Some(version),
+        None => sqlx::query_scalar("SHOW server_version")
+            .fetch_optional(&mut *conn)
+            .await
+            .context("Failed to fetch database version")?,
+    };
     let db_host: Option<String> =
         sqlx::query_scalar("SELECT COALESCE(inet_server_addr()::text, 'local')")
             .fetch_optional(&mut *conn)
@@ -235,12 +516,73 @@ async fn fetch_postgres_health_info(conn: &mut sqlx::PgConnection) -> Result<Pos
     .flatten();
 
     Ok(PostgresHealthInfo {
+        engine,
         version,
         db_host,
         uptime_seconds,
     })
 }
 
+/// `CockroachDB` has no `inet_server_addr()` nor postmaster, the host is the
+/// node the connection landed on (`n3`) and the uptime the one of that node
+async fn fetch_cockroach_health_info(
+    conn: &mut sqlx::PgConnection,
+    engine_version: &str,
+) -> PostgresHealthInfo {
+    let node = sqlx::query_as::<_, (i64, Option<i64>)>(
+        r"
+        SELECT node_id::int8, EXTRACT(EPOCH FROM now() - started_at)::int8
+        FROM crdb_internal.gossip_nodes
+        WHERE node_id = crdb_internal.node_id()
+        ",
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .ok()
+    .flatten();
+
+    PostgresHealthInfo {
+        engine: Engine::Cockroach,
+        version: Engine::Cockroach.version(engine_version),
+        db_host: node.map(|(node_id, _)| format!("n{node_id}")),
+        uptime_seconds: node.and_then(|(_, uptime)| uptime),
+    }
+}
+
+/// Nodes of a `CockroachDB` cluster by liveness, live `YugabyteDB` tablet servers
+async fn update_cluster_nodes(target: &str, conn: &mut sqlx::PgConnection, engine: Engine) {
+    let nodes = match engine {
+        Engine::Postgres => return,
+        Engine::Cockroach => sqlx::query_as::<_, (i64, i64, i64)>(
+            r"
+            SELECT count(*) FILTER (WHERE n.is_live AND NOT (l.draining OR l.decommissioning)),
+                   count(*) FILTER (WHERE n.is_live AND (l.draining OR l.decommissioning)),
+                   count(*) FILTER (WHERE NOT n.is_live)
+            FROM crdb_internal.gossip_liveness l
+            JOIN crdb_internal.gossip_nodes n USING (node_id)
+            WHERE l.membership <> 'decommissioned'
+            ",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map(|(live, draining, unavailable)| ClusterNodes {
+            live,
+            draining: Some(draining),
+            unavailable: Some(unavailable),
+        }),
+        Engine::Yugabyte => sqlx::query_scalar::<_, i64>("SELECT count(*) FROM yb_servers()")
+            .fetch_one(&mut *conn)
+            .await
+            .map(|live| ClusterNodes {
+                live,
+                ..ClusterNodes::default()
+            }),
+    };
+    if let Ok(nodes) = nodes {
+        engine::record(target, "postgres", nodes);
+    }
+}
+
 async fn postgres_is_in_recovery(conn: &mut sqlx::PgConnection) -> Result<bool> {
     let (is_in_recovery,): (bool,) = sqlx::query_as("SELECT pg_is_in_recovery();")
         .fetch_one(&mut *conn)
@@ -255,46 +597,262 @@ async fn postgres_transaction_is_read_only(conn: &mut sqlx::PgConnection) -> Res
     Ok(tx_read_only.eq_ignore_ascii_case("on"))
 }
 
-async fn maybe_record_postgres_replication_lag(conn: &mut sqlx::PgConnection) {
-    if let Ok(Some(lag_seconds)) = sqlx::query_scalar::<_, f64>(
-        "SELECT EXTRACT(EPOCH FROM (NOW() - pg_last_xact_replay_timestamp()))",
+async fn maybe_record_postgres_replication_lag(
+    target: &str,
+    conn: &mut sqlx::PgConnection,
+) -> Option<f64> {
+    let lag_seconds = sqlx::query_scalar::<_, Option<f64>>(
+        "SELECT EXTRACT(EPOCH FROM (NOW() - pg_last_xact_replay_timestamp()))::float8",
     )
-    .fetch_optional(&mut *conn)
+    .fetch_one(&mut *conn)
     .await
-    {
-        REPLICATION_LAG
-            .with_label_values(&["postgres"])
-            .observe(lag_seconds);
+    .ok()
+    .flatten()?;
+    REPLICATION_LAG
+        .with_label_values(&[target, "postgres"])
+        .observe(lag_seconds);
+    Some(lag_seconds)
+}
+
+/// Lag of every standby and WAL retained by every slot, seen from the primary
+///
+/// Standbys sharing an `application_name` are reported by the worst of them.
+/// Series of standbys and slots that are gone are removed; if a query fails
+/// (e.g. missing `pg_monitor`) the previous values are kept.
+async fn update_postgres_replication_metrics(target: &str, conn: &mut sqlx::PgConnection) {
+    let standbys = sqlx::query_as::<_, (String, f64, f64, f64, i64, i64)>(
+        r"
+        SELECT application_name,
+               COALESCE(max(EXTRACT(EPOCH FROM write_lag)), 0)::float8,
+               COALESCE(max(EXTRACT(EPOCH FROM flush_lag)), 0)::float8,
+               COALESCE(max(EXTRACT(EPOCH FROM replay_lag)), 0)::float8,
+               COALESCE(max(pg_wal_lsn_diff(pg_current_wal_lsn(), sent_lsn)), 0)::int8,
+               COALESCE(max(pg_wal_lsn_diff(pg_current_wal_lsn(), replay_lsn)), 0)::int8
+        FROM pg_stat_replication
+        WHERE sent_lsn IS NOT NULL
+        GROUP BY application_name
+        ",
+    )
+    .fetch_all(&mut *conn)
+    .await;
+    if let Ok(standbys) = standbys {
+        let (mut seconds, mut bytes) = (HashSet::new(), HashSet::new());
+        for (name, write, flush, replay, sent_bytes, replay_bytes) in standbys {
+            for (stage, lag) in [("write", write), ("flush", flush), ("replay", replay)] {
+                let labels = [target, "postgres", name.as_str(), stage];
+                STANDBY_LAG_SECONDS.vec.with_label_values(&labels).set(lag);
+                seconds.insert(labels.map(str::to_string).to_vec());
+            }
+            for (stage, lag) in [("sent", sent_bytes), ("replay", replay_bytes)] {
+                let labels = [target, "postgres", name.as_str(), stage];
+                STANDBY_LAG_BYTES.vec.with_label_values(&labels).set(lag);
+                bytes.insert(labels.map(str::to_string).to_vec());
+            }
+        }
+        STANDBY_LAG_SECONDS.replace(target, seconds);
+        STANDBY_LAG_BYTES.replace(target, bytes);
+    }
+
+    let slots = sqlx::query_as::<_, (String, String, bool, i64)>(
+        r"
+        SELECT slot_name::text,
+               slot_type,
+               active,
+               COALESCE(pg_wal_lsn_diff(pg_current_wal_lsn(), restart_lsn), 0)::int8
+        FROM pg_replication_slots
+        ",
+    )
+    .fetch_all(&mut *conn)
+    .await;
+    if let Ok(slots) = slots {
+        let mut reported = HashSet::new();
+        for (slot, slot_type, active, retained) in slots {
+            let labels = [target, "postgres", slot.as_str(), slot_type.as_str()];
+            REPLICATION_SLOT_RETAINED_BYTES
+                .vec
+                .with_label_values(&labels)
+                .set(retained);
+            REPLICATION_SLOT_ACTIVE
+                .vec
+                .with_label_values(&labels)
+                .set(i64::from(active));
+            reported.insert(labels.map(str::to_string).to_vec());
+        }
+        REPLICATION_SLOT_RETAINED_BYTES.replace(target, reported.clone());
+        REPLICATION_SLOT_ACTIVE.replace(target, reported);
     }
 }
 
 async fn postgres_read_only_result(
-    dsn: &DSN,
-    tls: &TlsConfig,
+    target: &Target,
     conn: &mut sqlx::PgConnection,
     cert_cache: &CertCache,
     health_info: PostgresHealthInfo,
+    replication_lag_seconds: Option<f64>,
     reason: &str,
 ) -> Result<HealthCheckResult> {
-    let tls_metadata = maybe_extract_postgres_tls(dsn, tls, conn, cert_cache).await;
+    let tls_metadata =
+        maybe_extract_postgres_tls(target, conn, cert_cache, health_info.engine).await;
     Ok(HealthCheckResult {
         version: format!("{} - {reason}", health_info.version.unwrap_or_default()),
+        flavor: health_info.engine.as_str(),
         db_host: health_info.db_host,
         uptime_seconds: health_info.uptime_seconds,
+        replication_lag_seconds,
         tls_metadata,
     })
 }
 
-async fn monitor_postgres_blocking_queries(conn: &mut sqlx::PgConnection) {
-    if let Ok(Some(blocking_count)) = sqlx::query_scalar::<_, i64>(
-        "SELECT COUNT(*) FROM pg_stat_activity WHERE wait_event_type = 'Lock' AND state = 'active'",
+/// Snapshot lock waits, the oldest transaction and the top blockers
+async fn monitor_postgres_locks(target: &str, conn: &mut sqlx::PgConnection) {
+    let Ok((blocked_sessions, oldest_blocked_seconds, oldest_transaction_seconds)) =
+        sqlx::query_as::<_, (i64, Option<f64>, Option<f64>)>(
+            r"
+            SELECT count(*) FILTER (WHERE cardinality(pg_blocking_pids(pid)) > 0),
+                   EXTRACT(EPOCH FROM max(now() - query_start)
+                       FILTER (WHERE cardinality(pg_blocking_pids(pid)) > 0))::float8,
+                   EXTRACT(EPOCH FROM max(now() - xact_start))::float8
+            FROM pg_stat_activity
+            WHERE backend_type = 'client backend' AND pid <> pg_backend_pid()
+            ",
+        )
+        .fetch_one(&mut *conn)
+        .await
+    else {
+        return;
+    };
+
+    let blockers = sqlx::query_as::<_, (i64, Option<String>, Option<String>, i64, Option<f64>)>(
+        r"
+        SELECT b.pid::int8,
+               b.usename::text,
+               NULLIF(b.application_name, ''),
+               count(*)::int8 AS blocked,
+               EXTRACT(EPOCH FROM now() - b.xact_start)::float8
+        FROM pg_stat_activity w
+        CROSS JOIN LATERAL unnest(pg_blocking_pids(w.pid)) AS blocking(pid)
+        JOIN pg_stat_activity b ON b.pid = blocking.pid
+        GROUP BY b.pid, b.usename, b.application_name, b.xact_start
+        ORDER BY blocked DESC, b.xact_start
+        LIMIT $1
+        ",
     )
-    .fetch_optional(&mut *conn)
+    .bind(locks::MAX_BLOCKERS)
+    .fetch_all(&mut *conn)
     .await
+    .unwrap_or_default();
+
+    locks::record(
+        target,
+        "postgres",
+        LockReport {
+            time: Utc::now(),
+            blocked_sessions,
+            oldest_blocked_seconds,
+            oldest_transaction_seconds,
+            blockers: blockers.into_iter().map(postgres_blocker).collect(),
+        },
+    );
+}
+
+fn postgres_blocker(
+    (pid, user, client, blocked_sessions, transaction_seconds): (
+        i64,
+        Option<String>,
+        Option<String>,
+        i64,
+        Option<f64>,
+    ),
+) -> Blocker {
+    Blocker {
+        pid,
+        user,
+        client,
+        blocked_sessions,
+        transaction_seconds,
+    }
+}
+
+/// Name the sessions holding locks on the test table if `error` is a lock timeout
+async fn explain_postgres_lock_timeout(
+    conn: &mut sqlx::PgConnection,
+    table_name: &str,
+    error: anyhow::Error,
+) -> anyhow::Error {
+    let lock_timeout = error.chain().any(|cause| {
+        cause
+            .downcast_ref::<sqlx::Error>()
+            .and_then(sqlx::Error::as_database_error)
+            .and_then(DatabaseError::code)
+            .is_some_and(|code| code == "55P03")
+    });
+    if !lock_timeout {
+        return error;
+    }
+
+    let holders = sqlx::query_as::<_, (i64, Option<String>, Option<String>, i64, Option<f64>)>(
+        r"
+        SELECT DISTINCT a.pid::int8,
+               a.usename::text,
+               NULLIF(a.application_name, ''),
+               0::int8,
+               EXTRACT(EPOCH FROM now() - a.xact_start)::float8
+        FROM pg_locks l
+        JOIN pg_stat_activity a ON a.pid = l.pid
+        WHERE l.relation = to_regclass($1) AND l.granted AND l.pid <> pg_backend_pid()
+        ORDER BY 5 DESC NULLS LAST
+        LIMIT $2
+        ",
+    )
+    .bind(table_name)
+    .bind(locks::MAX_BLOCKERS)
+    .fetch_all(&mut *conn)
+    .await
+    .unwrap_or_default();
+    if holders.is_empty() {
+        return error;
+    }
+    let holders: Vec<Blocker> = holders.into_iter().map(postgres_blocker).collect();
+    error.context(locks::held_by(table_name, &holders))
+}
+
+async fn monitor_postgres_connections(
+    target: &str,
+    conn: &mut sqlx::PgConnection,
+    idle_threshold: Duration,
+) {
+    if let Ok((current, active, idle, idle_in_transaction, long_idle, max)) =
+        sqlx::query_as::<_, (i64, i64, i64, i64, i64, i64)>(
+            r"
+            SELECT count(*),
+                   count(*) FILTER (WHERE state = 'active'),
+                   count(*) FILTER (WHERE state = 'idle'),
+                   count(*) FILTER (WHERE state LIKE 'idle in transaction%'),
+                   count(*) FILTER (WHERE state LIKE 'idle in transaction%'
+                                    AND state_change < now() - make_interval(secs => $1)),
+                   current_setting('max_connections')::int8
+            FROM pg_stat_activity
+            WHERE backend_type = 'client backend'
+            ",
+        )
+        .bind(idle_threshold.as_secs_f64())
+        .fetch_one(&mut *conn)
+        .await
     {
-        BLOCKING_QUERIES
-            .with_label_values(&["postgres"])
-            .set(blocking_count);
+        let labels = [target, "postgres"];
+        CONNECTIONS_CURRENT.with_label_values(&labels).set(current);
+        CONNECTIONS_MAX.with_label_values(&labels).set(max);
+        LONG_IDLE_IN_TRANSACTION
+            .with_label_values(&labels)
+            .set(long_idle);
+        for (state, count) in CONNECTION_STATES
+            .into_iter()
+            .zip([active, idle, idle_in_transaction])
+        {
+            CONNECTIONS_BY_STATE
+                .with_label_values(&[target, "postgres", state])
+                .set(count);
+        }
     }
 }
 
@@ -306,7 +864,11 @@ fn is_ignorable_postgres_create_error(error: &sqlx::Error) -> bool {
     }
 }
 
-async fn ensure_postgres_table(conn: &mut sqlx::PgConnection, table_name: &str) -> Result<()> {
+async fn ensure_postgres_table(
+    target: &str,
+    conn: &mut sqlx::PgConnection,
+    table_name: &str,
+) -> Result<()> {
     let create_table_sql = format!(
         r"
         CREATE TABLE IF NOT EXISTS {table_name} (
@@ -325,7 +887,7 @@ async fn ensure_postgres_table(conn: &mut sqlx::PgConnection, table_name: &str)
         return Err(error.into());
     }
     OPERATION_DURATION
-        .with_label_values(&["postgres", "create_table"])
+        .with_label_values(&[target, "postgres", "create_table"])
         .observe(create_table_timer.elapsed().as_secs_f64());
 
     let create_index_sql =
@@ -338,11 +900,12 @@ async fn ensure_postgres_table(conn: &mut sqlx::PgConnection, table_name: &str)
 }
 
 async fn postgres_insert_and_verify(
+    target: &str,
     conn: &mut sqlx::PgConnection,
     now: DateTime<Utc>,
     range: u32,
     table_name: &str,
-) -> Result<u32> {
+) -> Result<WrittenRow> {
     let id: u32 = rand::rng().random_range(0..range);
     let id_i32 = i32::try_from(id).context("generated id out of range for PostgreSQL INT")?;
     let uuid = Uuid::new_v4();
@@ -362,11 +925,12 @@ async fn postgres_insert_and_verify(
         .bind(uuid)
         .execute(&mut *conn)
         .await?;
+    let committed = Instant::now();
     OPERATION_DURATION
-        .with_label_values(&["postgres", "insert"])
+        .with_label_values(&[target, "postgres", "insert"])
         .observe(insert_timer.elapsed().as_secs_f64());
     ROWS_AFFECTED
-        .with_label_values(&["postgres", "insert"])
+        .with_label_values(&[target, "postgres", "insert"])
         .inc_by(insert_result.rows_affected());
 
     let select_sql = format!("SELECT t1, uuid FROM {table_name} WHERE id = $1");
@@ -376,7 +940,7 @@ async fn postgres_insert_and_verify(
         .fetch_optional(&mut *conn)
         .await?;
     OPERATION_DURATION
-        .with_label_values(&["postgres", "select"])
+        .with_label_values(&[target, "postgres", "select"])
         .observe(select_timer.elapsed().as_secs_f64());
 
     let (t1, v4) = row.context("Expected records")?;
@@ -390,10 +954,71 @@ async fn postgres_insert_and_verify(
         ));
     }
 
-    Ok(id)
+    Ok(WrittenRow {
+        id,
+        uuid,
+        committed,
+    })
+}
+
+/// Connect to a replica, before the row it is polled for is written
+async fn connect_postgres_replica(
+    replica: &Replica,
+    timeouts: Timeouts,
+) -> Result<sqlx::PgConnection> {
+    let options = postgres_connect_options(&replica.dsn, &replica.tls);
+    let mut conn = options.connect().await.context("Failed to connect")?;
+    set_postgres_session_timeouts(&mut conn, timeouts).await?;
+    Ok(conn)
+}
+
+/// Poll a replica until the row written on the primary is readable
+async fn wait_for_postgres_replica(
+    mut conn: sqlx::PgConnection,
+    table_name: &str,
+    row: WrittenRow,
+) -> Result<()> {
+    let id = i32::try_from(row.id).context("id out of range for PostgreSQL INT")?;
+    let select_sql = format!("SELECT uuid FROM {table_name} WHERE id = $1");
+    loop {
+        let uuid: Option<Uuid> = sqlx::query_scalar(&select_sql)
+            .bind(id)
+            .fetch_optional(&mut conn)
+            .await?;
+        if uuid == Some(row.uuid) {
+            break;
+        }
+        time::sleep(replica::POLL_INTERVAL).await;
+    }
+    let _ = conn.close().await;
+    Ok(())
+}
+
+/// Run the rollback test, again after serialization failures on engines that expect them
+async fn retry_postgres_rollback_test(
+    target: &str,
+    conn: &mut sqlx::PgConnection,
+    now: DateTime<Utc>,
+    table_name: &str,
+    engine: Engine,
+) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match postgres_transaction_rollback_test(target, conn, now, table_name).await {
+            Err(error)
+                if engine.retries_transactions()
+                    && attempt < engine::MAX_TRANSACTION_ATTEMPTS
+                    && engine::is_retryable(&error) =>
+            {
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
 }
 
 async fn postgres_transaction_rollback_test(
+    target: &str,
     conn: &mut sqlx::PgConnection,
     now: DateTime<Utc>,
     table_name: &str,
@@ -443,27 +1068,35 @@ async fn postgres_transaction_rollback_test(
     }
 
     OPERATION_DURATION
-        .with_label_values(&["postgres", "transaction_test"])
+        .with_label_values(&[target, "postgres", "transaction_test"])
         .observe(transaction_timer.elapsed().as_secs_f64());
     Ok(())
 }
 
-async fn postgres_cleanup_old_records(conn: &mut sqlx::PgConnection, table_name: &str) {
+async fn postgres_cleanup_old_records(
+    target: &str,
+    conn: &mut sqlx::PgConnection,
+    table_name: &str,
+) {
     let delete_old_sql = format!(
         "DELETE FROM {table_name} WHERE id IN (SELECT id FROM {table_name} WHERE t2 < NOW() - INTERVAL '1 hour' LIMIT 10000)"
     );
     let cleanup_timer = Instant::now();
     if let Ok(delete_result) = sqlx::query(&delete_old_sql).execute(&mut *conn).await {
         ROWS_AFFECTED
-            .with_label_values(&["postgres", "delete"])
+            .with_label_values(&[target, "postgres", "delete"])
             .inc_by(delete_result.rows_affected());
     }
     OPERATION_DURATION
-        .with_label_values(&["postgres", "cleanup"])
+        .with_label_values(&[target, "postgres", "cleanup"])
         .observe(cleanup_timer.elapsed().as_secs_f64());
 }
 
-async fn update_postgres_table_rows_metric(conn: &mut sqlx::PgConnection, table_name: &str) {
+async fn update_postgres_table_rows_metric(
+    target: &str,
+    conn: &mut sqlx::PgConnection,
+    table_name: &str,
+) {
     let row_count_sql = format!(
         "SELECT c.reltuples::bigint FROM pg_class c \
          JOIN pg_namespace n ON c.relnamespace = n.oid \
@@ -473,9 +1106,7 @@ async fn update_postgres_table_rows_metric(conn: &mut sqlx::PgConnection, table_
         .fetch_optional(&mut *conn)
         .await
     {
-        TABLE_ROWS
-            .with_label_values(&["postgres", table_name])
-            .set(row_count);
+        TABLE_ROWS.set_single(target, &[target, "postgres", table_name], row_count);
     }
 }
 
@@ -498,15 +1129,17 @@ async fn maybe_drop_postgres_table_hourly(
     }
 }
 
-async fn update_postgres_size_metrics(conn: &mut sqlx::PgConnection, table_name: &str) {
+async fn update_postgres_size_metrics(
+    target: &str,
+    conn: &mut sqlx::PgConnection,
+    table_name: &str,
+) {
     let size_sql = format!("SELECT pg_total_relation_size('{table_name}')");
     if let Ok(Some(table_bytes)) = sqlx::query_scalar::<_, i64>(&size_sql)
         .fetch_optional(&mut *conn)
         .await
     {
-        TABLE_SIZE_BYTES
-            .with_label_values(&["postgres", table_name])
-            .set(table_bytes);
+        TABLE_SIZE_BYTES.set_single(target, &[target, "postgres", table_name], table_bytes);
     }
 
     if let Ok(Some(db_size)) =
@@ -515,19 +1148,85 @@ async fn update_postgres_size_metrics(conn: &mut sqlx::PgConnection, table_name:
             .await
     {
         DATABASE_SIZE_BYTES
-            .with_label_values(&["postgres"])
+            .with_label_values(&[target, "postgres"])
             .set(db_size);
     }
 }
 
+/// Collect XID and `MultiXact` ages, autovacuum workers and dead tuples on the slow cadence
+async fn maybe_update_postgres_vacuum_metrics(target: &str, conn: &mut sqlx::PgConnection) {
+    if !VACUUM_CADENCE.due(target, SLOW_COLLECTOR_INTERVAL) {
+        return;
+    }
+
+    let Ok(databases) = sqlx::query_as::<_, (String, i64, i64, f64)>(
+        r"
+        SELECT datname::text,
+               age(datfrozenxid)::int8,
+               mxid_age(datminmxid)::int8,
+               (100.0 * age(datfrozenxid)
+                   / current_setting('autovacuum_freeze_max_age')::int8)::float8
+        FROM pg_database
+        ",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    else {
+        return;
+    };
+
+    let autovacuum_workers = sqlx::query_scalar::<_, i64>(
+        "SELECT count(*) FROM pg_stat_activity WHERE backend_type = 'autovacuum worker'",
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .unwrap_or_default();
+
+    let dead_tuples = sqlx::query_as::<_, (String, i64)>(
+        r"
+        SELECT format('%s.%s', schemaname, relname), n_dead_tup
+        FROM pg_stat_user_tables
+        WHERE n_dead_tup > 0
+        ORDER BY n_dead_tup DESC
+        LIMIT $1
+        ",
+    )
+    .bind(vacuum::MAX_DEAD_TUPLE_TABLES)
+    .fetch_all(&mut *conn)
+    .await
+    .ok();
+
+    vacuum::record(
+        target,
+        "postgres",
+        &VacuumReport {
+            databases: databases
+                .into_iter()
+                .map(
+                    |(name, xid_age, multixact_age, wraparound_percent)| DatabaseAge {
+                        name,
+                        xid_age,
+                        multixact_age,
+                        wraparound_percent,
+                    },
+                )
+                .collect(),
+            autovacuum_workers,
+            dead_tuples,
+        },
+    );
+}
+
 async fn maybe_extract_postgres_tls(
-    dsn: &DSN,
-    tls: &TlsConfig,
+    target: &Target,
     conn: &mut sqlx::PgConnection,
     cert_cache: &CertCache,
+    engine: Engine,
 ) -> Option<TlsMetadata> {
-    if tls.mode.is_enabled() {
-        extract_tls_metadata(dsn, tls, conn, cert_cache).await.ok()
+    if target.tls.mode.is_enabled() {
+        extract_tls_metadata(target, conn, cert_cache, engine)
+            .await
+            .ok()
     } else {
         None
     }
@@ -535,15 +1234,20 @@ async fn maybe_extract_postgres_tls(
 
 /// Extract TLS metadata from `PostgreSQL` connection
 async fn extract_tls_metadata(
-    dsn: &DSN,
-    tls: &TlsConfig,
+    target: &Target,
     conn: &mut sqlx::PgConnection,
     cert_cache: &CertCache,
+    engine: Engine,
 ) -> Result<TlsMetadata> {
+    let (dsn, tls) = (hosts::selected(target), &target.tls);
     // Query pg_stat_ssl for TLS information
-    let row = sqlx::query("SELECT version, cipher FROM pg_stat_ssl WHERE pid = pg_backend_pid()")
-        .fetch_optional(conn)
-        .await?;
+    let row = if engine.supports(Probe::SslStats) {
+        sqlx::query("SELECT version, cipher FROM pg_stat_ssl WHERE pid = pg_backend_pid()")
+            .fetch_optional(conn)
+            .await?
+    } else {
+        None
+    };
 
     let mut metadata = row.map_or_else(TlsMetadata::default, |row| {
         let version: Option<String> = row.try_get(0).ok();
@@ -557,7 +1261,8 @@ async fn extract_tls_metadata(
     });
 
     if tls.mode.is_enabled() {
-        match get_cert_metadata_cached(dsn, 5432, TlsProbeProtocol::Postgres, tls, cert_cache).await
+        let port = Engine::from_driver(&dsn.driver).default_port();
+        match get_cert_metadata_cached(dsn, port, TlsProbeProtocol::Postgres, tls, cert_cache).await
         {
             Ok(Some(probe_metadata)) => {
                 // Merge probe metadata (subject, issuer, expiry) with connection metadata (version, cipher)
@@ -585,7 +1290,7 @@ async fn extract_tls_metadata(
                     "unknown"
                 };
                 TLS_CERT_PROBE_ERRORS
-                    .with_label_values(&["postgres", error_type])
+                    .with_label_values(&[target.name.as_str(), "postgres", error_type])
                     .inc();
             }
         }
@@ -0,0 +1,318 @@
+//! `ProxySQL` admin interface
+//!
+//! `proxysql://` targets connect to the admin interface, an `SQLite`
+//! database behind the `MySQL` protocol that only understands plain text
+//! queries. The status of every backend and the connections `ProxySQL`
+//! keeps to it are read from `stats_mysql_connection_pool`. The check fails
+//! when no backend is `ONLINE`, nothing can be served through the proxy;
+//! the read/write test through it is a regular `mysql://` target.
+
+use super::{
+    CheckError, DeadlineExceeded, ErrorKind, HealthCheckResult,
+    hosts::{self, HostRole},
+    mysql::{mysql_column_text, mysql_connect_options, server_error_kind},
+    resolve_host,
+};
+use crate::{
+    metrics::{
+        OPERATION_DURATION, PROXYSQL_BACKEND_CONNECTION_ERRORS, PROXYSQL_BACKEND_CONNECTIONS,
+        PROXYSQL_BACKEND_INFO, PROXYSQL_BACKEND_LATENCY_SECONDS, PROXYSQL_BACKEND_QUERIES,
+    },
+    target::Target,
+    tls::ensure_crypto_provider,
+};
+use anyhow::{Context, Result, anyhow};
+use dsn::DSN;
+use sqlx::{ConnectOptions, Connection, Executor, mysql::MySqlConnection};
+use std::{collections::HashSet, time::Instant};
+use tokio::time;
+
+/// Port of the admin interface when the DSN does not give one
+pub(crate) const DEFAULT_PORT: u16 = 6032;
+
+/// States of `dbpulse_proxysql_backend_connections`, in the order of [`Backend::connections`]
+const CONNECTION_STATES: [&str; 2] = ["used", "free"];
+
+/// A row of `stats_mysql_connection_pool`, one per backend and hostgroup
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Backend {
+    pub(crate) hostgroup: String,
+    /// `srv_host:srv_port`
+    pub(crate) address: String,
+    /// `ONLINE`, `SHUNNED`, `OFFLINE_SOFT` or `OFFLINE_HARD`
+    pub(crate) status: String,
+    /// `ConnUsed`, `ConnFree`
+    pub(crate) connections: [i64; 2],
+    /// `ConnERR`
+    pub(crate) connection_errors: i64,
+    pub(crate) queries: i64,
+    /// `Latency_us`
+    pub(crate) latency_seconds: f64,
+}
+
+impl Backend {
+    /// Parse a row from its columns by name
+    pub(crate) fn parse(column: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let number = |name: &str| {
+            column(name)
+                .and_then(|value| value.trim().parse::<i64>().ok())
+                .unwrap_or(0)
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let latency_seconds = number("Latency_us") as f64 / 1_000_000.0;
+        Some(Self {
+            hostgroup: column("hostgroup")?,
+            address: format!("{}:{}", column("srv_host")?, column("srv_port")?),
+            status: column("status").unwrap_or_default(),
+            connections: ["ConnUsed", "ConnFree"].map(number),
+            connection_errors: number("ConnERR"),
+            queries: number("Queries"),
+            latency_seconds,
+        })
+    }
+}
+
+/// Why nothing can be served through the proxy, `None` if a backend is `ONLINE`
+///
+/// A proxy without backends is not configured yet and is not reported.
+pub(crate) fn unavailable(backends: &[Backend]) -> Option<String> {
+    if backends.is_empty() || backends.iter().any(|backend| backend.status == "ONLINE") {
+        return None;
+    }
+    let statuses: Vec<String> = backends
+        .iter()
+        .map(|backend| format!("{} is {}", backend.address, backend.status))
+        .collect();
+    Some(format!("no backend is ONLINE: {}", statuses.join(", ")))
+}
+
+/// Check the admin interface of the target and export its backends
+///
+/// # Errors
+///
+/// Returns an error if the admin interface cannot be reached or queried, or
+/// if no backend is `ONLINE`
+pub async fn test_admin(target: &Target) -> Result<HealthCheckResult> {
+    let result = async {
+        let mut conn = hosts::connect(target, "proxysql", |dsn| open_admin(target, dsn)).await?;
+        let result = collect(&target.name, &mut conn).await;
+        let _ = conn.close().await;
+        result
+    }
+    .await;
+    result.map_err(|error| CheckError::classify(error, server_error_kind, ErrorKind::Query))
+}
+
+/// Connect to the admin interface of one of the listed hosts
+///
+/// The admin interface rejects the session settings the driver sends by default.
+async fn open_admin(target: &Target, dsn: &DSN) -> Result<(MySqlConnection, HostRole)> {
+    ensure_crypto_provider();
+    let options = mysql_connect_options(dsn, &target.tls)
+        .port(dsn.port.unwrap_or(DEFAULT_PORT))
+        .pipes_as_concat(false)
+        .no_engine_substitution(false)
+        .timezone(None)
+        .set_names(false);
+    let connect = async {
+        resolve_host(&target.name, "proxysql", dsn, DEFAULT_PORT).await?;
+        let timer = Instant::now();
+        let conn = options.connect().await?;
+        OPERATION_DURATION
+            .with_label_values(&[target.name.as_str(), "proxysql", "connect"])
+            .observe(timer.elapsed().as_secs_f64());
+        anyhow::Ok(conn)
+    };
+    let conn = time::timeout(target.timeouts.connect, connect)
+        .await
+        .map_err(|_| DeadlineExceeded {
+            operation: "connect",
+            limit: target.timeouts.connect,
+        })??;
+    Ok((conn, HostRole::default()))
+}
+
+async fn collect(target: &str, conn: &mut MySqlConnection) -> Result<HealthCheckResult> {
+    let version = (&mut *conn)
+        .fetch_optional(sqlx::raw_sql(
+            "SELECT variable_value FROM global_variables WHERE variable_name = 'admin-version'",
+        ))
+        .await
+        .context("Failed to fetch ProxySQL version")?
+        .and_then(|row| mysql_column_text(&row, "variable_value"))
+        .map_or_else(
+            || "ProxySQL".to_string(),
+            |version| format!("ProxySQL {version}"),
+        );
+
+    let uptime_seconds = (&mut *conn)
+        .fetch_optional(sqlx::raw_sql(
+            "SELECT Variable_Value FROM stats_mysql_global WHERE Variable_Name = 'ProxySQL_Uptime'",
+        ))
+        .await
+        .ok()
+        .flatten()
+        .and_then(|row| mysql_column_text(&row, "Variable_Value"))
+        .and_then(|uptime| uptime.parse().ok());
+
+    let backends = (&mut *conn).fetch_all(sqlx::raw_sql(
+        "SELECT hostgroup, srv_host, srv_port, status, ConnUsed, ConnFree, ConnERR, Queries, Latency_us \
+         FROM stats_mysql_connection_pool",
+    ))
+    .await
+    .context("Failed to fetch ProxySQL connection pool")?
+    .iter()
+    .filter_map(|row| Backend::parse(|name| mysql_column_text(row, name)))
+    .collect::<Vec<_>>();
+    record(target, "proxysql", &backends);
+    if let Some(reason) = unavailable(&backends) {
+        return Err(CheckError::new(ErrorKind::Connection, anyhow!(reason)).into());
+    }
+
+    Ok(HealthCheckResult {
+        version,
+        flavor: "proxysql",
+        db_host: None,
+        uptime_seconds,
+        replication_lag_seconds: None,
+        tls_metadata: None,
+    })
+}
+
+/// Export the backends, removing the ones no longer listed
+pub(crate) fn record(target: &str, database: &str, backends: &[Backend]) {
+    let (mut statuses, mut connections, mut series) =
+        (HashSet::new(), HashSet::new(), HashSet::new());
+    for backend in backends {
+        let labels = [
+            target,
+            database,
+            backend.hostgroup.as_str(),
+            backend.address.as_str(),
+        ];
+        let status = [labels.as_slice(), &[backend.status.as_str()]].concat();
+        PROXYSQL_BACKEND_INFO.vec.with_label_values(&status).set(1);
+        statuses.insert(status.iter().map(ToString::to_string).collect());
+        for (state, count) in CONNECTION_STATES.into_iter().zip(backend.connections) {
+            let labels = [labels.as_slice(), &[state]].concat();
+            PROXYSQL_BACKEND_CONNECTIONS
+                .vec
+                .with_label_values(&labels)
+                .set(count);
+            connections.insert(labels.iter().map(ToString::to_string).collect());
+        }
+        PROXYSQL_BACKEND_CONNECTION_ERRORS
+            .vec
+            .with_label_values(&labels)
+            .set(backend.connection_errors);
+        PROXYSQL_BACKEND_QUERIES
+            .vec
+            .with_label_values(&labels)
+            .set(backend.queries);
+        PROXYSQL_BACKEND_LATENCY_SECONDS
+            .vec
+            .with_label_values(&labels)
+            .set(backend.latency_seconds);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    PROXYSQL_BACKEND_INFO.replace(target, statuses);
+    PROXYSQL_BACKEND_CONNECTIONS.replace(target, connections);
+    PROXYSQL_BACKEND_CONNECTION_ERRORS.replace(target, series.clone());
+    PROXYSQL_BACKEND_QUERIES.replace(target, series.clone());
+    PROXYSQL_BACKEND_LATENCY_SECONDS.replace(target, series);
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+    use std::collections::HashMap;
+
+    fn backend(address: &str, status: &str) -> Backend {
+        Backend {
+            hostgroup: "10".to_string(),
+            address: address.to_string(),
+            status: status.to_string(),
+            connections: [3, 7],
+            connection_errors: 2,
+            queries: 1500,
+            latency_seconds: 0.000_25,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let row: HashMap<&str, &str> = [
+            ("hostgroup", "10"),
+            ("srv_host", "db-1"),
+            ("srv_port", "3306"),
+            ("status", "SHUNNED"),
+            ("ConnUsed", "3"),
+            ("ConnFree", "7"),
+            ("ConnERR", "2"),
+            ("Queries", "1500"),
+            ("Latency_us", "250"),
+        ]
+        .into_iter()
+        .collect();
+        let parsed = Backend::parse(|name| row.get(name).map(ToString::to_string)).unwrap();
+        assert_eq!(parsed, backend("db-1:3306", "SHUNNED"));
+        assert_eq!(Backend::parse(|_| None), None);
+    }
+
+    #[test]
+    fn test_unavailable() {
+        assert_eq!(unavailable(&[]), None);
+        assert_eq!(
+            unavailable(&[
+                backend("db-1:3306", "SHUNNED"),
+                backend("db-2:3306", "ONLINE")
+            ]),
+            None
+        );
+        assert_eq!(
+            unavailable(&[
+                backend("db-1:3306", "SHUNNED"),
+                backend("db-2:3306", "OFFLINE_HARD")
+            ])
+            .unwrap(),
+            "no backend is ONLINE: db-1:3306 is SHUNNED, db-2:3306 is OFFLINE_HARD"
+        );
+    }
+
+    #[test]
+    fn test_record() {
+        record(
+            "proxy",
+            "proxysql",
+            &[
+                backend("db-1:3306", "ONLINE"),
+                backend("db-2:3306", "SHUNNED"),
+            ],
+        );
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_proxysql_backend_info{backend="db-2:3306",database="proxysql",hostgroup="10",status="SHUNNED",target="proxy"} 1"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_proxysql_backend_connections{backend="db-1:3306",database="proxysql",hostgroup="10",state="free",target="proxy"} 7"#
+        ));
+
+        // db-2 came back online
+        record(
+            "proxy",
+            "proxysql",
+            &[
+                backend("db-1:3306", "ONLINE"),
+                backend("db-2:3306", "ONLINE"),
+            ],
+        );
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"status="SHUNNED",target="proxy""#));
+        assert!(output.contains(
+            r#"dbpulse_proxysql_backend_info{backend="db-2:3306",database="proxysql",hostgroup="10",status="ONLINE",target="proxy"} 1"#
+        ));
+    }
+}
@@ -0,0 +1,593 @@
+//! `SQLite` databases of local services
+//!
+//! `sqlite:///path/to/db` targets open the database file directly, there is
+//! no server in between: the read/write test runs the same insert, verify and
+//! rollback cycle as the other drivers on the file itself. The size of the
+//! file and of its write-ahead log, the journal mode and the results of
+//! `PRAGMA quick_check` and `integrity_check` are exported; the integrity
+//! checks read the whole database, so they run on a slow cadence.
+//!
+//! `?mode=ro` opens the database read-only: the read/write test is skipped
+//! and the check reports a read-only database, like a standby.
+
+use super::{
+    CheckError, DeadlineExceeded, ErrorKind, HealthCheckResult,
+    cadence::{INTEGRITY_CHECK_CADENCE, QUICK_CHECK_CADENCE, SLOW_COLLECTOR_INTERVAL},
+    session::{Session, Slot},
+};
+use crate::{
+    custom::{ValueType, integer_value},
+    metrics::{
+        DATABASE_SIZE_BYTES, OPERATION_DURATION, ROWS_AFFECTED, SQLITE_INTEGRITY_OK,
+        SQLITE_JOURNAL_MODE, SQLITE_WAL_SIZE_BYTES, TABLE_ROWS,
+    },
+    target::{Target, Timeouts},
+};
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc, prelude::*};
+use dsn::DSN;
+use rand::Rng;
+use sqlx::{
+    ConnectOptions, Connection, SqliteConnection,
+    error::DatabaseError,
+    sqlite::{SqliteConnectOptions, SqliteError},
+};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+use tokio::{fs, time};
+use uuid::Uuid;
+
+/// Minimum time between two full `PRAGMA integrity_check` of the same target
+const INTEGRITY_CHECK_INTERVAL: Duration = Duration::from_hours(1);
+
+/// Test read/write operations on the table configured for the target
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened or operations fail
+pub async fn test_rw(target: &Target, now: DateTime<Utc>) -> Result<HealthCheckResult> {
+    test_rw_with_table(target, now, &target.table).await
+}
+
+/// Test read/write operations on a specified table
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened or operations fail
+pub async fn test_rw_with_table(
+    target: &Target,
+    now: DateTime<Utc>,
+    table_name: &str,
+) -> Result<HealthCheckResult> {
+    let mut session = Session::default();
+    let result = check_rw(target, now, table_name, &mut session.sqlite).await;
+    session.close().await;
+    result.map_err(classify)
+}
+
+/// Test read/write operations reusing the connection kept in `session`
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened or operations fail
+pub async fn test_rw_in_session(
+    target: &Target,
+    now: DateTime<Utc>,
+    session: &mut Session,
+) -> Result<HealthCheckResult> {
+    check_rw(target, now, &target.table, &mut session.sqlite)
+        .await
+        .map_err(classify)
+}
+
+fn classify(error: anyhow::Error) -> anyhow::Error {
+    CheckError::classify(error, server_error_kind, ErrorKind::Query)
+}
+
+async fn check_rw(
+    target: &Target,
+    now: DateTime<Utc>,
+    table_name: &str,
+    slot: &mut Slot<SqliteConnection>,
+) -> Result<HealthCheckResult> {
+    let (mut conn, opened) = if let Some(live) = slot.reuse(target, "sqlite").await {
+        live
+    } else {
+        let opened = Instant::now();
+        let conn = open_sqlite(target).await?;
+        slot.opened(target, "sqlite");
+        (conn, opened)
+    };
+    let result = run_sqlite_checks(target, &mut conn, now, table_name).await;
+    slot.release(target, "sqlite", conn, opened, result.is_ok())
+        .await;
+    result
+}
+
+/// Open the database file, creating it unless it is opened read-only
+async fn open_sqlite(target: &Target) -> Result<SqliteConnection> {
+    let options = sqlite_connect_options(&target.dsn, target.timeouts);
+    let connect = async {
+        let timer = Instant::now();
+        let conn = options.connect().await?;
+        OPERATION_DURATION
+            .with_label_values(&[target.name.as_str(), "sqlite", "connect"])
+            .observe(timer.elapsed().as_secs_f64());
+        anyhow::Ok(conn)
+    };
+    time::timeout(target.timeouts.connect, connect)
+        .await
+        .map_err(|_| DeadlineExceeded {
+            operation: "connect",
+            limit: target.timeouts.connect,
+        })?
+}
+
+/// Options to open the database file of a DSN
+///
+/// Writers wait up to the lock timeout for the database to be unlocked; the
+/// journal mode is left as the service using the database configured it. A
+/// missing file fails the check unless `?mode=rwc` asks for it to be created:
+/// an empty database in its place would look healthy.
+pub(crate) fn sqlite_connect_options(dsn: &DSN, timeouts: Timeouts) -> SqliteConnectOptions {
+    SqliteConnectOptions::new()
+        .filename(&dsn.address)
+        .read_only(is_read_only(dsn))
+        .create_if_missing(dsn.params.get("mode").is_some_and(|mode| mode == "rwc"))
+        .busy_timeout(timeouts.lock)
+}
+
+/// Whether the DSN opens the database read-only, `?mode=ro`
+fn is_read_only(dsn: &DSN) -> bool {
+    dsn.params.get("mode").is_some_and(|mode| mode == "ro")
+}
+
+/// Category of an `SQLite` error, by primary result code
+pub(crate) fn server_error_kind(error: &dyn DatabaseError) -> Option<ErrorKind> {
+    let code = error.try_downcast_ref::<SqliteError>()?.code()?;
+    // extended result codes keep the primary one in the low byte
+    Some(match code.parse::<i32>().ok()? & 0xff {
+        // SQLITE_PERM, SQLITE_AUTH
+        3 | 23 => ErrorKind::Authentication,
+        // SQLITE_BUSY, SQLITE_LOCKED: still locked after the busy timeout
+        5 | 6 => ErrorKind::Timeout,
+        8 => ErrorKind::ReadOnly,
+        13 => ErrorKind::DiskFull,
+        // SQLITE_CANTOPEN
+        14 => ErrorKind::Connection,
+        _ => return None,
+    })
+}
+
+async fn run_sqlite_checks(
+    target: &Target,
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+    table_name: &str,
+) -> Result<HealthCheckResult> {
+    let name = target.name.as_str();
+    let version: String = sqlx::query_scalar("SELECT sqlite_version()")
+        .fetch_one(&mut *conn)
+        .await
+        .context("Failed to fetch database version")?;
+    let version = format!("SQLite {version}");
+    maybe_run_sqlite_integrity_checks(name, conn).await;
+
+    if is_read_only(&target.dsn) {
+        update_sqlite_file_metrics(name, conn, &target.dsn.address).await;
+        return Ok(sqlite_result(format!(
+            "{version} - Database opened read-only"
+        )));
+    }
+
+    ensure_sqlite_table(name, conn, table_name).await?;
+    let id = sqlite_insert_and_verify(name, conn, now, target.range, table_name).await?;
+    sqlite_transaction_rollback_test(name, conn, now, table_name)
+        .await
+        .map_err(|error| CheckError::classify(error, server_error_kind, ErrorKind::Transaction))?;
+    sqlite_cleanup_old_records(name, conn, table_name).await;
+    maybe_drop_sqlite_table_hourly(conn, now, id, table_name).await;
+    update_sqlite_table_rows_metric(name, conn, table_name).await;
+    update_sqlite_file_metrics(name, conn, &target.dsn.address).await;
+
+    Ok(sqlite_result(version))
+}
+
+const fn sqlite_result(version: String) -> HealthCheckResult {
+    HealthCheckResult {
+        version,
+        flavor: "sqlite",
+        db_host: None,
+        uptime_seconds: None,
+        replication_lag_seconds: None,
+        tls_metadata: None,
+    }
+}
+
+/// Run the query of a custom check and return its scalar result
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be opened or the query fails, the
+/// query returns no row or `NULL`, or the value does not have the expected type
+pub(crate) async fn custom_check_value(
+    target: &Target,
+    sql: &str,
+    value_type: ValueType,
+) -> Result<f64> {
+    let mut conn = open_sqlite(target).await?;
+    let value = fetch_sqlite_scalar(&mut conn, sql, value_type).await;
+    let _ = conn.close().await;
+    value.map_err(classify)
+}
+
+async fn fetch_sqlite_scalar(
+    conn: &mut SqliteConnection,
+    sql: &str,
+    value_type: ValueType,
+) -> Result<f64> {
+    let value = match value_type {
+        ValueType::Integer => sqlx::query_scalar::<_, Option<i64>>(sql)
+            .fetch_one(&mut *conn)
+            .await?
+            .map(integer_value),
+        ValueType::Float => {
+            sqlx::query_scalar::<_, Option<f64>>(sql)
+                .fetch_one(&mut *conn)
+                .await?
+        }
+        ValueType::Boolean => sqlx::query_scalar::<_, Option<bool>>(sql)
+            .fetch_one(&mut *conn)
+            .await?
+            .map(|value| if value { 1.0 } else { 0.0 }),
+    };
+    value.context("Query returned NULL")
+}
+
+async fn ensure_sqlite_table(
+    target: &str,
+    conn: &mut SqliteConnection,
+    table_name: &str,
+) -> Result<()> {
+    let create_table_sql = format!(
+        r"
+        CREATE TABLE IF NOT EXISTS {table_name} (
+            id INTEGER NOT NULL PRIMARY KEY,
+            t1 INTEGER NOT NULL,
+            t2 TEXT NOT NULL DEFAULT (strftime('%Y-%m-%d %H:%M:%f', 'now')),
+            uuid BLOB NOT NULL UNIQUE
+        )
+        "
+    );
+    let create_table_timer = Instant::now();
+    sqlx::query(&create_table_sql).execute(&mut *conn).await?;
+    OPERATION_DURATION
+        .with_label_values(&[target, "sqlite", "create_table"])
+        .observe(create_table_timer.elapsed().as_secs_f64());
+
+    let create_index_sql =
+        format!("CREATE INDEX IF NOT EXISTS idx_{table_name}_t2 ON {table_name}(t2)");
+    sqlx::query(&create_index_sql)
+        .execute(&mut *conn)
+        .await
+        .ok();
+    Ok(())
+}
+
+async fn sqlite_insert_and_verify(
+    target: &str,
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+    range: u32,
+    table_name: &str,
+) -> Result<u32> {
+    let id: u32 = rand::rng().random_range(0..range);
+    let uuid = Uuid::new_v4();
+
+    let insert_sql = format!(
+        r"
+        INSERT INTO {table_name} (id, t1, uuid)
+        VALUES (?, ?, ?)
+        ON CONFLICT (id)
+        DO UPDATE SET t1 = excluded.t1, uuid = excluded.uuid
+        "
+    );
+    let insert_timer = Instant::now();
+    let insert_result = sqlx::query(&insert_sql)
+        .bind(i64::from(id))
+        .bind(now.timestamp())
+        .bind(uuid)
+        .execute(&mut *conn)
+        .await?;
+    OPERATION_DURATION
+        .with_label_values(&[target, "sqlite", "insert"])
+        .observe(insert_timer.elapsed().as_secs_f64());
+    ROWS_AFFECTED
+        .with_label_values(&[target, "sqlite", "insert"])
+        .inc_by(insert_result.rows_affected());
+
+    let select_sql = format!("SELECT t1, uuid FROM {table_name} WHERE id = ?");
+    let select_timer = Instant::now();
+    let row: Option<(i64, Uuid)> = sqlx::query_as(&select_sql)
+        .bind(i64::from(id))
+        .fetch_optional(&mut *conn)
+        .await?;
+    OPERATION_DURATION
+        .with_label_values(&[target, "sqlite", "select"])
+        .observe(select_timer.elapsed().as_secs_f64());
+
+    let (t1, v4) = row.context("Expected records")?;
+    if now.timestamp() != t1 || uuid != v4 {
+        return Err(anyhow!(
+            "Records don't match: expected ({}, {}), got ({}, {})",
+            now.timestamp(),
+            uuid,
+            t1,
+            v4
+        ));
+    }
+
+    Ok(id)
+}
+
+async fn sqlite_transaction_rollback_test(
+    target: &str,
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+    table_name: &str,
+) -> Result<()> {
+    let rollback_test_id = now.timestamp_micros().rem_euclid(i64::from(i32::MAX));
+    let rollback_uuid = Uuid::new_v4();
+
+    let transaction_timer = Instant::now();
+    let mut tx = conn.begin().await?;
+    let insert_tx_sql = format!(
+        "INSERT INTO {table_name} (id, t1, uuid) VALUES (?, 999, ?) ON CONFLICT (id) DO UPDATE SET t1 = 999"
+    );
+    sqlx::query(&insert_tx_sql)
+        .bind(rollback_test_id)
+        .bind(rollback_uuid)
+        .execute(tx.as_mut())
+        .await?;
+
+    let update_tx_sql = format!("UPDATE {table_name} SET t1 = ? WHERE id = ?");
+    sqlx::query(&update_tx_sql)
+        .bind(0)
+        .bind(rollback_test_id)
+        .execute(tx.as_mut())
+        .await?;
+
+    let select_tx_sql = format!("SELECT t1 FROM {table_name} WHERE id = ?");
+    let updated_value: Option<i64> = sqlx::query_scalar(&select_tx_sql)
+        .bind(rollback_test_id)
+        .fetch_optional(tx.as_mut())
+        .await?;
+    if updated_value != Some(0) {
+        return Err(anyhow!(
+            "Transaction update failed: expected 0, got {updated_value:?}"
+        ));
+    }
+    tx.rollback().await?;
+
+    let rolled_back_value: Option<i64> = sqlx::query_scalar(&select_tx_sql)
+        .bind(rollback_test_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if rolled_back_value == Some(0) {
+        return Err(anyhow!("Transaction rollback failed: value is still 0"));
+    }
+
+    OPERATION_DURATION
+        .with_label_values(&[target, "sqlite", "transaction_test"])
+        .observe(transaction_timer.elapsed().as_secs_f64());
+    Ok(())
+}
+
+async fn sqlite_cleanup_old_records(target: &str, conn: &mut SqliteConnection, table_name: &str) {
+    let delete_old_sql = format!(
+        "DELETE FROM {table_name} WHERE id IN (SELECT id FROM {table_name} \
+         WHERE t2 < strftime('%Y-%m-%d %H:%M:%f', 'now', '-1 hour') LIMIT 10000)"
+    );
+    let cleanup_timer = Instant::now();
+    if let Ok(delete_result) = sqlx::query(&delete_old_sql).execute(&mut *conn).await {
+        ROWS_AFFECTED
+            .with_label_values(&[target, "sqlite", "delete"])
+            .inc_by(delete_result.rows_affected());
+    }
+    OPERATION_DURATION
+        .with_label_values(&[target, "sqlite", "cleanup"])
+        .observe(cleanup_timer.elapsed().as_secs_f64());
+}
+
+async fn maybe_drop_sqlite_table_hourly(
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+    id: u32,
+    table_name: &str,
+) {
+    if now.minute() == 0 && id < 5 {
+        let count_sql = format!("SELECT COUNT(*) FROM {table_name}");
+        if let Ok(Some(exact_count)) = sqlx::query_scalar::<_, i64>(&count_sql)
+            .fetch_optional(&mut *conn)
+            .await
+            && exact_count < 100_000
+        {
+            let drop_table_sql = format!("DROP TABLE IF EXISTS {table_name}");
+            sqlx::query(&drop_table_sql).execute(&mut *conn).await.ok();
+        }
+    }
+}
+
+/// Rows of the table, counted: `SQLite` keeps no estimate
+async fn update_sqlite_table_rows_metric(
+    target: &str,
+    conn: &mut SqliteConnection,
+    table_name: &str,
+) {
+    let row_count_sql = format!("SELECT COUNT(*) FROM {table_name}");
+    if let Ok(row_count) = sqlx::query_scalar::<_, i64>(&row_count_sql)
+        .fetch_one(&mut *conn)
+        .await
+    {
+        TABLE_ROWS.set_single(target, &[target, "sqlite", table_name], row_count);
+    }
+}
+
+/// Export the journal mode and the size of the database and write-ahead log files
+async fn update_sqlite_file_metrics(target: &str, conn: &mut SqliteConnection, path: &str) {
+    if let Ok(mode) = sqlx::query_scalar::<_, String>("PRAGMA journal_mode")
+        .fetch_one(&mut *conn)
+        .await
+    {
+        SQLITE_JOURNAL_MODE.set_single(target, &[target, "sqlite", mode.as_str()], 1);
+    }
+
+    if let Ok(Some(db_size)) = file_size(path).await {
+        DATABASE_SIZE_BYTES
+            .with_label_values(&[target, "sqlite"])
+            .set(db_size);
+    }
+    if let Ok(wal_size) = file_size(&format!("{path}-wal")).await {
+        SQLITE_WAL_SIZE_BYTES
+            .with_label_values(&[target, "sqlite"])
+            .set(wal_size.unwrap_or(0));
+    }
+}
+
+/// Size of a file in bytes, `None` if it does not exist
+async fn file_size(path: &str) -> io::Result<Option<i64>> {
+    match fs::metadata(path).await {
+        Ok(metadata) => Ok(Some(i64::try_from(metadata.len()).unwrap_or(i64::MAX))),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Run `PRAGMA quick_check` and `integrity_check` when they are due
+///
+/// Corruption is exported, not reported as a failed check: the database may
+/// still serve the rows the service reads and writes.
+async fn maybe_run_sqlite_integrity_checks(target: &str, conn: &mut SqliteConnection) {
+    for (check, cadence, every) in [
+        ("quick_check", &QUICK_CHECK_CADENCE, SLOW_COLLECTOR_INTERVAL),
+        (
+            "integrity_check",
+            &INTEGRITY_CHECK_CADENCE,
+            INTEGRITY_CHECK_INTERVAL,
+        ),
+    ] {
+        if !cadence.due(target, every) {
+            continue;
+        }
+        let timer = Instant::now();
+        match sqlx::query_scalar::<_, String>(&format!("PRAGMA {check}"))
+            .fetch_all(&mut *conn)
+            .await
+        {
+            Ok(messages) => {
+                OPERATION_DURATION
+                    .with_label_values(&[target, "sqlite", check])
+                    .observe(timer.elapsed().as_secs_f64());
+                let ok = integrity_ok(&messages);
+                if !ok {
+                    eprintln!(
+                        "PRAGMA {check} of '{target}' failed: {}",
+                        messages.join("; ")
+                    );
+                }
+                SQLITE_INTEGRITY_OK
+                    .with_label_values(&[target, "sqlite", check])
+                    .set(i64::from(ok));
+            }
+            Err(e) => eprintln!("Error running PRAGMA {check} on '{target}': {e}"),
+        }
+    }
+}
+
+/// Whether an integrity check found no problem, it then returns a single `ok` row
+fn integrity_ok(messages: &[String]) -> bool {
+    matches!(messages, [message] if message == "ok")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+    use std::path::Path;
+
+    fn target(path: &Path, params: &str) -> Target {
+        let path = path.display();
+        Target::for_test(&path.to_string(), &format!("sqlite://{path}{params}"))
+    }
+
+    #[test]
+    fn test_integrity_ok() {
+        assert!(integrity_ok(&["ok".to_string()]));
+        assert!(!integrity_ok(&[]));
+        assert!(!integrity_ok(&[
+            "*** in database main ***".to_string(),
+            "Page 5: never used".to_string()
+        ]));
+    }
+
+    #[tokio::test]
+    async fn test_rw_creates_database() {
+        let dir = std::env::temp_dir().join(format!("dbpulse-sqlite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.db");
+        let target = target(&path, "");
+
+        // a missing file is only created on request
+        let error = test_rw(&target, Utc::now()).await.unwrap_err();
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Connection, "{error:#}");
+        assert!(!path.exists());
+
+        let result = test_rw(&self::target(&path, "?mode=rwc"), Utc::now())
+            .await
+            .unwrap();
+        assert!(result.version.starts_with("SQLite 3."));
+        assert_eq!(result.flavor, "sqlite");
+        assert!(path.exists());
+
+        // the connection kept in a session is reused
+        let mut session = Session::default();
+        test_rw_in_session(&target, Utc::now(), &mut session)
+            .await
+            .unwrap();
+        test_rw_in_session(&target, Utc::now(), &mut session)
+            .await
+            .unwrap();
+        session.close().await;
+
+        let name = path.display().to_string();
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(&format!(
+            r#"dbpulse_sqlite_integrity_ok{{check="quick_check",database="sqlite",target="{name}"}} 1"#
+        )));
+        assert!(output.contains(&format!(
+            r#"dbpulse_sqlite_journal_mode_info{{database="sqlite",mode="delete",target="{name}"}} 1"#
+        )));
+
+        let read_only = test_rw(&self::target(&path, "?mode=ro"), Utc::now())
+            .await
+            .unwrap();
+        assert!(read_only.version.ends_with("Database opened read-only"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rw_errors() {
+        let missing = Path::new("/nonexistent/dbpulse/state.db");
+        let error = test_rw(&target(missing, ""), Utc::now()).await.unwrap_err();
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Connection);
+
+        let error = test_rw(&target(missing, "?mode=ro"), Utc::now())
+            .await
+            .unwrap_err();
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Connection);
+    }
+}
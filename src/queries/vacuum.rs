@@ -0,0 +1,131 @@
+//! Vacuum and transaction ID wraparound state of `PostgreSQL` primaries
+//!
+//! Collected best effort on the slow cadence: XID and `MultiXact` ages of
+//! every database, how close they are to `autovacuum_freeze_max_age`, the
+//! running autovacuum workers and the tables of the monitored database with
+//! the most dead tuples.
+
+use crate::metrics::{
+    POSTGRES_AUTOVACUUM_WORKERS, POSTGRES_DEAD_TUPLES, POSTGRES_MULTIXACT_AGE, POSTGRES_XID_AGE,
+    POSTGRES_XID_WRAPAROUND_PERCENT,
+};
+use std::collections::HashSet;
+
+/// Tables exported by `dbpulse_postgres_dead_tuples`, the ones with most dead tuples first
+pub(crate) const MAX_DEAD_TUPLE_TABLES: i64 = 10;
+
+/// Vacuum state seen by a check
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct VacuumReport {
+    pub(crate) databases: Vec<DatabaseAge>,
+    pub(crate) autovacuum_workers: i64,
+    /// `schema.table` and its dead tuples, `None` if they could not be read
+    pub(crate) dead_tuples: Option<Vec<(String, i64)>>,
+}
+
+/// Frozen XID and `MultiXact` ages of a database
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DatabaseAge {
+    pub(crate) name: String,
+    /// `age(datfrozenxid)`
+    pub(crate) xid_age: i64,
+    /// `mxid_age(datminmxid)`
+    pub(crate) multixact_age: i64,
+    /// `xid_age` as a percentage of `autovacuum_freeze_max_age`
+    pub(crate) wraparound_percent: f64,
+}
+
+/// Export a report as metrics, replacing the series of databases and tables no longer seen
+pub(crate) fn record(target: &str, database: &str, report: &VacuumReport) {
+    let mut series = HashSet::new();
+    for age in &report.databases {
+        let labels = [target, database, age.name.as_str()];
+        POSTGRES_XID_AGE
+            .vec
+            .with_label_values(&labels)
+            .set(age.xid_age);
+        POSTGRES_MULTIXACT_AGE
+            .vec
+            .with_label_values(&labels)
+            .set(age.multixact_age);
+        POSTGRES_XID_WRAPAROUND_PERCENT
+            .vec
+            .with_label_values(&labels)
+            .set(age.wraparound_percent);
+        series.insert(labels.map(str::to_string).to_vec());
+    }
+    POSTGRES_XID_AGE.replace(target, series.clone());
+    POSTGRES_MULTIXACT_AGE.replace(target, series.clone());
+    POSTGRES_XID_WRAPAROUND_PERCENT.replace(target, series);
+
+    POSTGRES_AUTOVACUUM_WORKERS
+        .with_label_values(&[target, database])
+        .set(report.autovacuum_workers);
+
+    if let Some(dead_tuples) = &report.dead_tuples {
+        let mut series = HashSet::new();
+        for (table, dead) in dead_tuples {
+            let labels = [target, database, table.as_str()];
+            POSTGRES_DEAD_TUPLES
+                .vec
+                .with_label_values(&labels)
+                .set(*dead);
+            series.insert(labels.map(str::to_string).to_vec());
+        }
+        POSTGRES_DEAD_TUPLES.replace(target, series);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+    use crate::metrics::encode_metrics;
+
+    fn age(name: &str, xid_age: i64) -> DatabaseAge {
+        DatabaseAge {
+            name: name.to_string(),
+            xid_age,
+            multixact_age: 12,
+            wraparound_percent: 50.0,
+        }
+    }
+
+    #[test]
+    fn test_record_replaces_series() {
+        record(
+            "vacuum-db",
+            "postgres",
+            &VacuumReport {
+                databases: vec![age("app", 100_000_000), age("old", 5)],
+                autovacuum_workers: 2,
+                dead_tuples: Some(vec![("public.orders".to_string(), 4_200)]),
+            },
+        );
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(output.contains(
+            r#"dbpulse_postgres_xid_age{database="postgres",datname="app",target="vacuum-db"} 100000000"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_postgres_dead_tuples{database="postgres",table="public.orders",target="vacuum-db"} 4200"#
+        ));
+        assert!(output.contains(
+            r#"dbpulse_postgres_autovacuum_workers{database="postgres",target="vacuum-db"} 2"#
+        ));
+
+        // dropped databases disappear, unreadable dead tuples keep their last values
+        record(
+            "vacuum-db",
+            "postgres",
+            &VacuumReport {
+                databases: vec![age("app", 100_000_100)],
+                autovacuum_workers: 0,
+                dead_tuples: None,
+            },
+        );
+        let output = String::from_utf8(encode_metrics().unwrap()).unwrap();
+        assert!(!output.contains(r#"datname="old",target="vacuum-db""#));
+        assert!(output.contains(r#"table="public.orders",target="vacuum-db""#));
+    }
+}
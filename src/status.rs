@@ -0,0 +1,398 @@
+//! Latest check results per target, served by `/health`, `/ready` and `/status`
+
+use crate::{
+    config::DEFAULT_READY_INTERVALS,
+    pulse::Pulse,
+    queries::{ErrorKind, HealthCheckResult, locks::LockReport},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{LazyLock, PoisonError, RwLock},
+};
+
+/// A loop is considered stuck after this many intervals without progress
+const STUCK_INTERVALS: i64 = 3;
+
+/// Extra time granted to slow iterations before a loop is considered stuck
+const STUCK_GRACE_SECONDS: i64 = 60;
+
+pub static STATUS: LazyLock<StatusBoard> = LazyLock::new(StatusBoard::default);
+
+/// Error of the last failed check
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct LastError {
+    pub time: DateTime<Utc>,
+    /// Same value as the `error_type` label of `dbpulse_errors_total`
+    #[serde(rename = "type")]
+    pub error_type: ErrorKind,
+    pub message: String,
+}
+
+/// State of a single target
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetStatus {
+    pub target: String,
+    pub database: &'static str,
+    pub interval: u16,
+    /// Whether the last check succeeded
+    pub healthy: bool,
+    /// Whether a check succeeded within the readiness window
+    pub ready: bool,
+    /// Last time the monitoring loop started or finished an iteration
+    pub heartbeat: DateTime<Utc>,
+    pub last_check: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub pulse: Option<Pulse>,
+    pub health: Option<HealthCheckResult>,
+    pub last_error: Option<LastError>,
+    /// Lock waits and blockers seen by the last check of a writable server
+    pub locks: Option<LockReport>,
+}
+
+impl TargetStatus {
+    fn is_ready(&self, now: DateTime<Utc>, ready_intervals: u32) -> bool {
+        let window = Duration::seconds(i64::from(self.interval) * i64::from(ready_intervals));
+        self.last_success
+            .is_some_and(|success| now.signed_duration_since(success) <= window)
+    }
+
+    fn is_stuck(&self, now: DateTime<Utc>) -> bool {
+        let limit =
+            Duration::seconds(i64::from(self.interval) * STUCK_INTERVALS + STUCK_GRACE_SECONDS);
+        now.signed_duration_since(self.heartbeat) > limit
+    }
+}
+
+/// Outcome of one check, as recorded by the monitoring loop
+pub struct Check<'a> {
+    pub time: DateTime<Utc>,
+    pub pulse: &'a Pulse,
+    pub result: Option<&'a HealthCheckResult>,
+    /// `(error_type, message)` if the check failed
+    pub error: Option<(ErrorKind, String)>,
+}
+
+/// Status of every monitored target
+#[derive(Debug)]
+pub struct StatusBoard {
+    targets: RwLock<BTreeMap<String, TargetStatus>>,
+    ready_intervals: RwLock<u32>,
+}
+
+impl Default for StatusBoard {
+    fn default() -> Self {
+        Self {
+            targets: RwLock::default(),
+            ready_intervals: RwLock::new(DEFAULT_READY_INTERVALS),
+        }
+    }
+}
+
+impl StatusBoard {
+    /// Start tracking a target, replacing any previous state
+    ///
+    /// The last success and error of a target restarted under the same name
+    /// are kept, so a reload does not make it unready until its next check.
+    pub fn register(&self, target: &str, database: &'static str, interval: u16) {
+        let mut targets = self.write();
+        let previous = targets.remove(target);
+        let status = TargetStatus {
+            target: target.to_string(),
+            database,
+            interval,
+            healthy: false,
+            ready: false,
+            heartbeat: Utc::now(),
+            last_check: None,
+            last_success: previous.as_ref().and_then(|status| status.last_success),
+            pulse: None,
+            health: None,
+            last_error: previous.and_then(|status| status.last_error),
+            locks: None,
+        };
+        targets.insert(target.to_string(), status);
+    }
+
+    /// Stop tracking a target
+    pub fn remove(&self, target: &str) {
+        self.write().remove(target);
+    }
+
+    /// Record that the monitoring loop of a target is making progress
+    pub fn heartbeat(&self, target: &str) {
+        if let Some(status) = self.write().get_mut(target) {
+            status.heartbeat = Utc::now();
+        }
+    }
+
+    /// Record the outcome of a check
+    pub fn record(&self, target: &str, check: Check<'_>) {
+        let mut targets = self.write();
+        let Some(status) = targets.get_mut(target) else {
+            return;
+        };
+
+        status.heartbeat = Utc::now();
+        status.last_check = Some(check.time);
+        status.pulse = Some(check.pulse.clone());
+        if let Some(result) = check.result {
+            status.health = Some(result.clone());
+        }
+        match check.error {
+            None => {
+                status.healthy = true;
+                status.last_success = Some(check.time);
+            }
+            Some((error_type, message)) => {
+                status.healthy = false;
+                status.last_error = Some(LastError {
+                    time: check.time,
+                    error_type,
+                    message,
+                });
+            }
+        }
+    }
+
+    /// Record the lock snapshot taken by a check, kept even if the check fails later
+    pub fn record_locks(&self, target: &str, report: LockReport) {
+        if let Some(status) = self.write().get_mut(target) {
+            status.locks = Some(report);
+        }
+    }
+
+    /// Number of intervals the last success may be old for a target to be ready
+    pub fn set_ready_intervals(&self, intervals: u32) {
+        *self
+            .ready_intervals
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = intervals;
+    }
+
+    /// Current state of every target, sorted by name
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<TargetStatus> {
+        self.snapshot_at(Utc::now())
+    }
+
+    fn snapshot_at(&self, now: DateTime<Utc>) -> Vec<TargetStatus> {
+        let ready_intervals = *self
+            .ready_intervals
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|status| TargetStatus {
+                ready: status.is_ready(now, ready_intervals),
+                ..status.clone()
+            })
+            .collect()
+    }
+
+    /// Current state of a single target
+    #[must_use]
+    pub fn get(&self, target: &str) -> Option<TargetStatus> {
+        self.snapshot()
+            .into_iter()
+            .find(|status| status.target == target)
+    }
+
+    /// Targets whose monitoring loop stopped making progress
+    #[must_use]
+    pub fn stuck(&self) -> Vec<String> {
+        let now = Utc::now();
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .filter(|status| status.is_stuck(now))
+            .map(|status| status.target.clone())
+            .collect()
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, TargetStatus>> {
+        self.targets.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+    use super::*;
+
+    fn result() -> HealthCheckResult {
+        HealthCheckResult {
+            version: "PostgreSQL 17.0".to_string(),
+            flavor: "postgres",
+            db_host: Some("db-1".to_string()),
+            uptime_seconds: Some(42),
+            replication_lag_seconds: None,
+            tls_metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_register_not_ready() {
+        let board = StatusBoard::default();
+        board.register("db-1", "postgres", 30);
+
+        let status = board.get("db-1").unwrap();
+        assert!(!status.healthy);
+        assert!(!status.ready);
+        assert!(status.last_check.is_none());
+        assert!(board.get("db-2").is_none());
+    }
+
+    #[test]
+    fn test_register_again_keeps_last_success() {
+        let board = StatusBoard::default();
+        board.register("db-1", "postgres", 30);
+        let now = Utc::now();
+        board.record(
+            "db-1",
+            Check {
+                time: now,
+                pulse: &Pulse::default(),
+                result: Some(&result()),
+                error: None,
+            },
+        );
+
+        // restarted by a reload with a new interval
+        board.register("db-1", "postgres", 10);
+        let status = board.get("db-1").unwrap();
+        assert_eq!(status.interval, 10);
+        assert_eq!(status.last_success, Some(now));
+        assert!(status.ready);
+        assert!(status.last_check.is_none());
+
+        board.remove("db-1");
+        board.register("db-1", "postgres", 10);
+        assert!(!board.get("db-1").unwrap().ready);
+    }
+
+    #[test]
+    fn test_record_success_and_failure() {
+        let board = StatusBoard::default();
+        board.register("db-1", "postgres", 30);
+        let pulse = Pulse::default();
+        let now = Utc::now();
+
+        board.record(
+            "db-1",
+            Check {
+                time: now,
+                pulse: &pulse,
+                result: Some(&result()),
+                error: None,
+            },
+        );
+        let status = board.get("db-1").unwrap();
+        assert!(status.healthy);
+        assert!(status.ready);
+        assert_eq!(status.last_success, Some(now));
+        assert_eq!(status.health.unwrap().version, "PostgreSQL 17.0");
+
+        board.record(
+            "db-1",
+            Check {
+                time: now,
+                pulse: &pulse,
+                result: None,
+                error: Some((ErrorKind::Connection, "connection refused".to_string())),
+            },
+        );
+        let status = board.get("db-1").unwrap();
+        assert!(!status.healthy);
+        // still ready: the last success is within the window
+        assert!(status.ready);
+        // the last successful result is kept
+        assert!(status.health.is_some());
+        let error = status.last_error.unwrap();
+        assert_eq!(error.error_type, ErrorKind::Connection);
+        assert_eq!(error.message, "connection refused");
+    }
+
+    #[test]
+    fn test_ready_window() {
+        let board = StatusBoard::default();
+        board.register("db-1", "mysql", 10);
+        board.set_ready_intervals(2);
+        let pulse = Pulse::default();
+        let now = Utc::now();
+
+        board.record(
+            "db-1",
+            Check {
+                time: now,
+                pulse: &pulse,
+                result: Some(&result()),
+                error: None,
+            },
+        );
+
+        let ready = |seconds| {
+            board
+                .snapshot_at(now + Duration::seconds(seconds))
+                .first()
+                .unwrap()
+                .ready
+        };
+        assert!(ready(20));
+        assert!(!ready(21));
+    }
+
+    #[test]
+    fn test_stuck() {
+        let board = StatusBoard::default();
+        board.register("db-1", "postgres", 10);
+        assert!(board.stuck().is_empty());
+
+        board.write().get_mut("db-1").unwrap().heartbeat = Utc::now() - Duration::seconds(91);
+        assert_eq!(board.stuck(), vec!["db-1".to_string()]);
+
+        board.heartbeat("db-1");
+        assert!(board.stuck().is_empty());
+    }
+
+    #[test]
+    fn test_remove() {
+        let board = StatusBoard::default();
+        board.register("db-1", "postgres", 10);
+        board.register("db-2", "mysql", 10);
+        board.remove("db-1");
+
+        let names: Vec<String> = board.snapshot().into_iter().map(|s| s.target).collect();
+        assert_eq!(names, vec!["db-2".to_string()]);
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        let board = StatusBoard::default();
+        board.register("db-1", "postgres", 30);
+        board.record(
+            "db-1",
+            Check {
+                time: Utc::now(),
+                pulse: &Pulse::default(),
+                result: None,
+                error: Some((
+                    ErrorKind::Authentication,
+                    "password authentication failed".to_string(),
+                )),
+            },
+        );
+
+        let json = serde_json::to_value(board.get("db-1").unwrap()).unwrap();
+        assert_eq!(json.pointer("/target").unwrap(), "db-1");
+        assert_eq!(json.pointer("/database").unwrap(), "postgres");
+        assert_eq!(json.pointer("/ready").unwrap(), false);
+        assert_eq!(json.pointer("/last_error/type").unwrap(), "authentication");
+        assert!(json.pointer("/last_success").unwrap().is_null());
+    }
+}
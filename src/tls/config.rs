@@ -1,7 +1,7 @@
 use std::{path::PathBuf, str::FromStr};
 
 /// TLS configuration for database connections
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct TlsConfig {
     pub mode: TlsMode,
     pub ca: Option<PathBuf>,
@@ -1,5 +1,7 @@
+use serde::Serialize;
+
 /// TLS connection metadata extracted after handshake
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct TlsMetadata {
     /// TLS protocol version (e.g., "TLSv1.3")
     pub version: Option<String>,
@@ -5,6 +5,7 @@ mod common;
 use chrono::Utc;
 use common::*;
 use dbpulse::queries::{mysql, postgres};
+use dbpulse::target::Target;
 use dbpulse::tls::TlsConfig;
 use dbpulse::tls::cache::CertCache;
 
@@ -60,11 +61,17 @@ async fn test_postgres_all_metrics_present() {
     let dsn = parse_dsn(POSTGRES_DSN);
     let now = Utc::now();
     let tls = TlsConfig::default();
-    let cert_cache = CertCache::new(std::time::Duration::from_secs(300));
+    let cert_cache = CertCache::new(std::time::Duration::from_mins(5));
     let table_name = test_table_name("metrics_test_postgres");
 
     // Run the health check
-    let result = postgres::test_rw_with_table(&dsn, now, 100, &tls, &cert_cache, &table_name).await;
+    let result = postgres::test_rw_with_table(
+        &Target::new(dsn, tls, 30, 100),
+        now,
+        &cert_cache,
+        &table_name,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "PostgreSQL health check failed: {:?}",
@@ -176,11 +183,17 @@ async fn test_mariadb_all_metrics_present() {
     let dsn = parse_dsn(MARIADB_DSN);
     let now = Utc::now();
     let tls = TlsConfig::default();
-    let cert_cache = CertCache::new(std::time::Duration::from_secs(300));
+    let cert_cache = CertCache::new(std::time::Duration::from_mins(5));
     let table_name = test_table_name("metrics_test_mariadb");
 
     // Run the health check
-    let result = mysql::test_rw_with_table(&dsn, now, 100, &tls, &cert_cache, &table_name).await;
+    let result = mysql::test_rw_with_table(
+        &Target::new(dsn, tls, 30, 100),
+        now,
+        &cert_cache,
+        &table_name,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "MariaDB health check failed: {:?}",
@@ -316,12 +329,16 @@ async fn test_postgres_and_mariadb_metric_parity() {
     let pg_dsn = parse_dsn(POSTGRES_DSN);
     let pg_now = Utc::now();
     let pg_tls = TlsConfig::default();
-    let pg_cert_cache = CertCache::new(std::time::Duration::from_secs(300));
+    let pg_cert_cache = CertCache::new(std::time::Duration::from_mins(5));
     let pg_table = test_table_name("parity_test_postgres");
 
-    let pg_result =
-        postgres::test_rw_with_table(&pg_dsn, pg_now, 100, &pg_tls, &pg_cert_cache, &pg_table)
-            .await;
+    let pg_result = postgres::test_rw_with_table(
+        &Target::new(pg_dsn, pg_tls, 30, 100),
+        pg_now,
+        &pg_cert_cache,
+        &pg_table,
+    )
+    .await;
     assert!(pg_result.is_ok(), "PostgreSQL test failed");
 
     let pg_encoded = dbpulse::metrics::encode_metrics().expect("Failed to encode PG metrics");
@@ -334,11 +351,16 @@ async fn test_postgres_and_mariadb_metric_parity() {
     let my_dsn = parse_dsn(MARIADB_DSN);
     let my_now = Utc::now();
     let my_tls = TlsConfig::default();
-    let my_cert_cache = CertCache::new(std::time::Duration::from_secs(300));
+    let my_cert_cache = CertCache::new(std::time::Duration::from_mins(5));
     let my_table = test_table_name("parity_test_mariadb");
 
-    let my_result =
-        mysql::test_rw_with_table(&my_dsn, my_now, 100, &my_tls, &my_cert_cache, &my_table).await;
+    let my_result = mysql::test_rw_with_table(
+        &Target::new(my_dsn, my_tls, 30, 100),
+        my_now,
+        &my_cert_cache,
+        &my_table,
+    )
+    .await;
     assert!(my_result.is_ok(), "MariaDB test failed");
 
     let my_encoded = dbpulse::metrics::encode_metrics().expect("Failed to encode MySQL metrics");
@@ -5,6 +5,7 @@ mod common;
 use chrono::Utc;
 use common::*;
 use dbpulse::queries::postgres;
+use dbpulse::target::Target;
 use dbpulse::tls::cache::CertCache;
 use dbpulse::tls::{TlsConfig, TlsMode};
 use std::fs::File;
@@ -31,9 +32,15 @@ async fn test_postgres_basic_connection() {
     let now = Utc::now();
     let tls = TlsConfig::default();
     let table_name = test_table_name("test_postgres_basic_connection");
-    let cert_cache = CertCache::new(std::time::Duration::from_secs(300));
-
-    let result = postgres::test_rw_with_table(&dsn, now, 100, &tls, &cert_cache, &table_name).await;
+    let cert_cache = CertCache::new(std::time::Duration::from_mins(5));
+
+    let result = postgres::test_rw_with_table(
+        &Target::new(dsn, tls, 30, 100),
+        now,
+        &cert_cache,
+        &table_name,
+    )
+    .await;
     assert!(
         result.is_ok(),
         "Failed to connect to PostgreSQL: {result:?}"
@@ -57,13 +64,13 @@ async fn test_postgres_read_write_operations() {
     let dsn = parse_dsn(POSTGRES_DSN);
     let now = Utc::now();
     let tls = TlsConfig::default();
-    let cert_cache = CertCache::new(std::time::Duration::from_secs(300));
+    let cert_cache = CertCache::new(std::time::Duration::from_mins(5));
+    let target = Target::new(dsn, tls, 30, 100);
 
     // Run test multiple times to ensure cleanup works
     for i in 0..5 {
         let table_name = test_table_name(&format!("test_postgres_read_write_operations_{i}"));
-        let result =
-            postgres::test_rw_with_table(&dsn, now, 100, &tls, &cert_cache, &table_name).await;
+        let result = postgres::test_rw_with_table(&target, now, &cert_cache, &table_name).await;
         let health = result.unwrap_or_else(|e| panic!("Iteration {i} failed: {e:?}"));
         assert_version_and_uptime("PostgreSQL", &health);
     }
@@ -80,10 +87,16 @@ async fn test_postgres_transaction_rollback() {
     let now = Utc::now();
     let tls = TlsConfig::default();
     let table_name = test_table_name("test_postgres_transaction_rollback");
-    let cert_cache = CertCache::new(std::time::Duration::from_secs(300));
+    let cert_cache = CertCache::new(std::time::Duration::from_mins(5));
 
     // This tests that transaction rollback works correctly
-    let result = postgres::test_rw_with_table(&dsn, now, 100, &tls, &cert_cache, &table_name).await;
+    let result = postgres::test_rw_with_table(
+        &Target::new(dsn, tls, 30, 100),
+        now,
+        &cert_cache,
+        &table_name,
+    )
+    .await;
     let health = result.unwrap_or_else(|e| panic!("Transaction test failed: {e:?}"));
     assert_version_and_uptime("PostgreSQL", &health);
 }
@@ -104,8 +117,14 @@ async fn test_postgres_concurrent_connections() {
             let dsn = parse_dsn(POSTGRES_DSN);
             let tls = TlsConfig::default();
             let now = Utc::now();
-            let cert_cache = CertCache::new(std::time::Duration::from_secs(300));
-            postgres::test_rw_with_table(&dsn, now, 100, &tls, &cert_cache, &table_name).await
+            let cert_cache = CertCache::new(std::time::Duration::from_mins(5));
+            postgres::test_rw_with_table(
+                &Target::new(dsn, tls, 30, 100),
+                now,
+                &cert_cache,
+                &table_name,
+            )
+            .await
         });
         handles.push(handle);
     }
@@ -130,13 +149,14 @@ async fn test_postgres_with_different_ranges() {
     let dsn = parse_dsn(POSTGRES_DSN);
     let now = Utc::now();
     let tls = TlsConfig::default();
-    let cert_cache = CertCache::new(std::time::Duration::from_secs(300));
+    let cert_cache = CertCache::new(std::time::Duration::from_mins(5));
+    let mut target = Target::new(dsn, tls, 30, 100);
 
     // Test different range values
     for range in [10, 50, 100, 500, 1000] {
+        target.range = range;
         let table_name = test_table_name(&format!("test_postgres_with_different_ranges_{range}"));
-        let result =
-            postgres::test_rw_with_table(&dsn, now, range, &tls, &cert_cache, &table_name).await;
+        let result = postgres::test_rw_with_table(&target, now, &cert_cache, &table_name).await;
         let health = result.unwrap_or_else(|e| panic!("Range {range} failed: {e:?}"));
         assert_version_and_uptime("PostgreSQL", &health);
     }
@@ -423,7 +443,7 @@ async fn test_postgres_pulse_transition_stop_start() {
         "Failed to start PostgreSQL container (dbpulse-postgres)"
     );
     assert!(
-        wait_for_pulse_value(port, 1, Duration::from_secs(60)).await,
+        wait_for_pulse_value(port, 1, Duration::from_mins(1)).await,
         "Expected pulse transition back to 1 after container start"
     );
 }
@@ -0,0 +1,155 @@
+//! `SQLite` targets, on database files in a temporary directory
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use chrono::Utc;
+use common::*;
+use dbpulse::queries::{ErrorKind, sqlite};
+use dbpulse::target::{self, Target};
+use dbpulse::tls::TlsConfig;
+use sqlx::{ConnectOptions, Connection, sqlite::SqliteConnectOptions};
+use std::{path::PathBuf, time::Duration};
+
+/// Directory of a test's databases, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(test_name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "dbpulse-{}-{}",
+            test_table_name(test_name),
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    /// Target of a database file, created by the first check
+    fn target(&self, file: &str) -> Target {
+        let dsn = format!("sqlite://{}?mode=rwc", self.0.join(file).display());
+        let dsn = target::parse_dsn(&dsn).unwrap().into_iter().next().unwrap();
+        Target::new(dsn, TlsConfig::default(), 30, 100)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[tokio::test]
+async fn test_sqlite_wal_database() {
+    let dir = TempDir::new("test_sqlite_wal_database");
+    let path = dir.0.join("service.db");
+
+    // the service owning the database enabled WAL and keeps it open
+    let mut service = SqliteConnectOptions::new()
+        .filename(&path)
+        .create_if_missing(true)
+        .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+        .connect()
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE jobs (id INTEGER PRIMARY KEY, state TEXT NOT NULL)")
+        .execute(&mut service)
+        .await
+        .unwrap();
+
+    let target = dir.target("service.db");
+    let table_name = test_table_name("test_sqlite_wal_database");
+    let result = sqlite::test_rw_with_table(&target, Utc::now(), &table_name).await;
+    assert!(result.is_ok(), "Failed to check SQLite: {result:?}");
+    let health = result.unwrap();
+    assert!(
+        health.version.starts_with("SQLite 3."),
+        "{}",
+        health.version
+    );
+    assert_eq!(health.flavor, "sqlite");
+
+    let name = path.display().to_string();
+    let output = String::from_utf8(dbpulse::metrics::encode_metrics().unwrap()).unwrap();
+    assert!(
+        output.contains(&format!(
+            r#"dbpulse_sqlite_journal_mode_info{{database="sqlite",mode="wal",target="{name}"}} 1"#
+        )),
+        "{output}"
+    );
+    assert!(output.contains(&format!(
+        r#"dbpulse_sqlite_integrity_ok{{check="integrity_check",database="sqlite",target="{name}"}} 1"#
+    )));
+    let wal_size = output
+        .lines()
+        .find(|line| {
+            line.starts_with(&format!(
+                r#"dbpulse_sqlite_wal_size_bytes{{database="sqlite",target="{name}"}}"#
+            ))
+        })
+        .and_then(|line| line.rsplit(' ').next())
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap();
+    assert!(wal_size > 0, "{output}");
+    assert!(output.contains(&format!(
+        r#"dbpulse_table_rows{{database="sqlite",table="{table_name}",target="{name}"}}"#
+    )));
+
+    service.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sqlite_missing_database() {
+    let dir = TempDir::new("test_sqlite_missing_database");
+    let mut target = dir.target("deleted.db");
+    target.dsn.params.remove("mode");
+
+    // a deleted volume must not be replaced by a new, empty database
+    let error = sqlite::test_rw(&target, Utc::now()).await.unwrap_err();
+    assert_eq!(ErrorKind::of(&error), ErrorKind::Connection, "{error:#}");
+    assert!(!dir.0.join("deleted.db").exists());
+}
+
+#[tokio::test]
+async fn test_sqlite_locked_database() {
+    let dir = TempDir::new("test_sqlite_locked_database");
+    let mut target = dir.target("locked.db");
+    sqlite::test_rw(&target, Utc::now()).await.unwrap();
+
+    // another writer holds the lock for longer than the lock timeout
+    let mut writer = SqliteConnectOptions::new()
+        .filename(dir.0.join("locked.db"))
+        .connect()
+        .await
+        .unwrap();
+    sqlx::query("BEGIN IMMEDIATE")
+        .execute(&mut writer)
+        .await
+        .unwrap();
+    target.timeouts.lock = Duration::from_millis(200);
+
+    let error = sqlite::test_rw(&target, Utc::now()).await.unwrap_err();
+    assert_eq!(ErrorKind::of(&error), ErrorKind::Timeout, "{error:#}");
+
+    sqlx::query("ROLLBACK").execute(&mut writer).await.unwrap();
+    writer.close().await.unwrap();
+    sqlite::test_rw(&target, Utc::now()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sqlite_read_only() {
+    let dir = TempDir::new("test_sqlite_read_only");
+    let mut target = dir.target("state.db");
+    sqlite::test_rw(&target, Utc::now()).await.unwrap();
+
+    target
+        .dsn
+        .params
+        .insert("mode".to_string(), "ro".to_string());
+    let health = sqlite::test_rw(&target, Utc::now()).await.unwrap();
+    assert!(
+        health.version.ends_with("Database opened read-only"),
+        "{}",
+        health.version
+    );
+}